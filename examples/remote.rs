@@ -0,0 +1,62 @@
+// Runs the one-way coordinator algorithm across local processes:
+//
+//   cargo run --release --example remote -- coordinator 127.0.0.1:4000 in/toy/x.npz 4
+//   cargo run --release --example remote -- worker 127.0.0.1:4000 in/toy/x.npz 4 0
+//   ...
+//   cargo run --release --example remote -- worker 127.0.0.1:4000 in/toy/x.npz 4 3
+//
+// Worker `m` takes the `m`-th contiguous chunk of the data.
+use std::{env, error::Error, fs, net::TcpListener};
+
+use ndarray::prelude::*;
+use ndarray_npy::NpzReader;
+use rsf::{adapter::remote::one_way_coordinator, prelude::*};
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = env::args().collect();
+    let [_, role, addr, path, n_machines, rest @ ..] = args.as_slice() else {
+        return Err("usage: remote coordinator|worker <addr> <npz> <n_machines> [machine]".into());
+    };
+    let n_machines: usize = n_machines.parse()?;
+
+    let mut npz = NpzReader::new(fs::File::open(path)?)?;
+    let x: Array2<f32> = npz.by_name("x.npy")?;
+    let y_true: Array1<bool> = npz.by_name("y.npy")?;
+    let n1 = y_true.iter().filter(|&&a| a).count();
+    let bb = x.outer_iter().bb().ok_or("no bounding box")?;
+    let cfg = ConfigBuilder::default()
+        .bounding_box(bb)
+        .n_trees(32)
+        .n_points(256)
+        .granularity(4)
+        .n_machines(n_machines)
+        .seed(0)
+        .build();
+
+    match role.as_str() {
+        "coordinator" => {
+            let listener = TcpListener::bind(addr)?;
+            let (f, anomalies, cost) = one_way_coordinator(&listener, &cfg, n1)?;
+            println!(
+                "points: {}, pr: {:.2}, received: {} B",
+                f.n_points(),
+                pr(&y_true, &anomalies),
+                cost.bytes()
+            );
+        }
+        "worker" => {
+            let machine: usize = rest.first().ok_or("no machine")?.parse()?;
+            let chunk_size = x.nrows().div_ceil(n_machines);
+            let offset = machine * chunk_size;
+            x.axis_chunks_iter(Axis(0), chunk_size)
+                .nth(machine)
+                .ok_or("no such machine")?
+                .outer_iter()
+                .enumerate()
+                .map(|(i, p)| (offset + i, p))
+                .one_way_worker(&cfg, n1, machine, addr.as_str())?;
+        }
+        _ => return Err(format!("unknown role {role}").into()),
+    }
+    Ok(())
+}
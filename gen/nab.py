@@ -1,4 +1,4 @@
-from git import Repo
+from subprocess import run
 from tempfile import TemporaryDirectory
 from shutil import move, rmtree
 from os import mkdir, makedirs
@@ -7,7 +7,7 @@ root = "in/nab"
 
 with TemporaryDirectory() as tmpdir:
     rmtree(root, ignore_errors=True)
-    Repo.clone_from("https://github.com/numenta/NAB", tmpdir)
+    run(["git", "clone", "https://github.com/numenta/NAB", tmpdir], check=True)
     move(f"{tmpdir}/data", f"{root}/data")
     mkdir(f"{root}/labels")
     move(f"{tmpdir}/labels/combined_windows.json",
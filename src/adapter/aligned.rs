@@ -0,0 +1,82 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+pub struct Counted<I> {
+    iter: I,
+    count: Arc<AtomicUsize>,
+}
+
+impl<I: Iterator> Iterator for Counted<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.iter.next();
+        if item.is_some() {
+            self.count.fetch_add(1, Ordering::Relaxed);
+        }
+        item
+    }
+}
+
+// Every output of the adapters is emitted right after the input it scores, so an output stands
+// for the last input consumed and every input consumed before it without an output is warm-up.
+// Pipelines that emit several outputs for one input, like `spotlight_edges` after a gap, break
+// this and panic.
+pub struct Aligned<J: Iterator> {
+    iter: J,
+    count: Arc<AtomicUsize>,
+    emitted: usize,
+    pending: Option<J::Item>,
+    done: bool,
+}
+
+impl<J: Iterator> Iterator for Aligned<J> {
+    type Item = Option<J::Item>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pending.is_none() && !self.done {
+            self.pending = self.iter.next();
+            self.done = self.pending.is_none();
+            assert!(
+                self.done || self.count.load(Ordering::Relaxed) > self.emitted,
+                "several outputs for one input"
+            );
+        }
+        let count = self.count.load(Ordering::Relaxed);
+        if self.emitted == count {
+            return None;
+        }
+        self.emitted += 1;
+        if self.emitted < count {
+            Some(None)
+        } else {
+            Some(self.pending.take())
+        }
+    }
+}
+
+pub trait AlignedIter: Iterator + Sized {
+    // Runs `pipeline` on the items and yields one `Option` per item, `None` during warm-up.
+    fn aligned<J, F>(self, pipeline: F) -> Aligned<J>
+    where
+        J: Iterator,
+        F: FnOnce(Counted<Self>) -> J,
+    {
+        let count = Arc::new(AtomicUsize::new(0));
+        let iter = pipeline(Counted {
+            iter: self,
+            count: count.clone(),
+        });
+        Aligned {
+            iter,
+            count,
+            emitted: 0,
+            pending: None,
+            done: false,
+        }
+    }
+}
+
+impl<I: Iterator> AlignedIter for I {}
@@ -0,0 +1,122 @@
+use ndarray::{ArrayBase, Data, Ix2};
+
+use crate::{
+    algorithm::{
+        forest::{RandShiftForest, RSF},
+        tree::RandShiftTree,
+    },
+    metric::exp_bst_path_length,
+    score::{AnomalyScore, PathLength},
+};
+
+#[derive(Clone)]
+pub enum Calibration {
+    Mass(f32),
+    Empirical(Vec<f32>),
+}
+
+impl Calibration {
+    pub fn from_mass(mass: f32) -> Self {
+        let n = mass.round().max(2.) as usize;
+        Calibration::Mass(exp_bst_path_length(n))
+    }
+
+    // Uses the weight the trees actually hold, which differs from `n_points` for hash-picked
+    // windows, sketched forests and trees with granularity > 1.
+    pub fn from_forest<T: RandShiftTree>(f: &RandShiftForest<T>) -> Self {
+        Self::from_mass(f.weight())
+    }
+
+    // Maps a raw score to the fraction of reference points that are less anomalous.
+    pub fn empirical<T: RandShiftTree, S: Data<Elem = f32>>(
+        f: &RandShiftForest<T>,
+        reference: &ArrayBase<S, Ix2>,
+    ) -> Self {
+        assert!(reference.dim().0 > 0, "empty reference sample");
+        let mut scores = f.batch_score(reference).mapv(PathLength::value).to_vec();
+        scores.sort_unstable_by(f32::total_cmp);
+        Calibration::Empirical(scores)
+    }
+
+    pub fn apply(&self, s: PathLength) -> AnomalyScore {
+        let s = s.value();
+        let p = match self {
+            Calibration::Mass(cn) => (-s / cn).exp2(),
+            Calibration::Empirical(scores) => {
+                let n_le = scores.partition_point(|&v| v <= s);
+                ((scores.len() - n_le) as f32) / (scores.len() as f32)
+            }
+        };
+        AnomalyScore::new(p)
+    }
+}
+
+pub struct Calibrate<I> {
+    iter: I,
+    cal: Calibration,
+}
+
+impl<I: Iterator<Item = PathLength>> Iterator for Calibrate<I> {
+    type Item = AnomalyScore;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|s| self.cal.apply(s))
+    }
+}
+
+// Score iterators backed by a forest that keeps changing, such as `rsf_window`.
+pub trait ForestScores: Iterator<Item = PathLength> {
+    fn forest(&self) -> &RSF;
+}
+
+// Mass calibration that follows the forest: it is taken from the forest at the first score and
+// again every time as many scores as its mass have been emitted, so a window that turns over is
+// tracked without walking every tree on every score.
+pub struct CalibrateByMass<I> {
+    iter: I,
+    cal: Option<Calibration>,
+    until: usize,
+}
+
+impl<I: ForestScores> CalibrateByMass<I> {
+    pub fn forest(&self) -> &RSF {
+        self.iter.forest()
+    }
+}
+
+impl<I: ForestScores> Iterator for CalibrateByMass<I> {
+    type Item = AnomalyScore;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let s = self.iter.next()?;
+        if self.until == 0 {
+            let f = self.iter.forest();
+            self.until = (f.weight().round() as usize).max(1);
+            self.cal = Some(Calibration::from_forest(f));
+        }
+        self.until -= 1;
+        self.cal.as_ref().map(|cal| cal.apply(s))
+    }
+}
+
+pub trait CalibrateIter: Iterator<Item = PathLength> + Sized {
+    fn calibrate(self, cal: &Calibration) -> Calibrate<Self> {
+        Calibrate {
+            iter: self,
+            cal: cal.clone(),
+        }
+    }
+
+    fn calibrate_by_mass(self) -> CalibrateByMass<Self>
+    where
+        Self: ForestScores,
+    {
+        CalibrateByMass {
+            iter: self,
+            cal: None,
+            until: 0,
+        }
+    }
+}
+
+impl<I: Iterator<Item = PathLength>> CalibrateIter for I {}
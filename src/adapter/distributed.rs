@@ -1,25 +1,174 @@
-use std::collections::HashMap;
+use std::{cmp::Reverse, collections::HashMap};
 
-use super::par_stream_sampler::ParStreamSampler;
+use super::{
+    par_stream_sampler::ParStreamSampler,
+    partition::{Partitioner, RandomRanges},
+};
 use crate::{
     algorithm::{config::Config, forest::RSF, tree::RandShiftTree},
-    metric::k_smallest,
+    metric::top_k,
+    score::PathLength,
 };
 use itertools::Itertools;
 use ndarray::{prelude::*, Data};
-use rand::{seq::SliceRandom, thread_rng};
+use rand::{prelude::StdRng, seq::SliceRandom, thread_rng, Rng, SeedableRng};
 use rand_distr::{Distribution, Uniform};
-use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
 
-fn retain(ps: &mut Vec<(usize, Array1<f32>)>, scores: &Array1<f32>, n1: usize) {
+fn retain(ps: &mut Vec<(usize, Array1<f32>)>, scores: &Array1<PathLength>, n1: usize) {
     let n = ps.len();
-    let most_anomalous = k_smallest(scores, n1);
+    let most_anomalous = top_k(scores, n1);
     let mut mask = Array1::from_elem(n, false);
     most_anomalous.into_iter().for_each(|i| mask[i] = true);
     let mut keep = mask.into_iter();
     ps.retain(|_| keep.next().unwrap());
 }
 
+// Sizes of what is sent, with coordinates and scores as f32 and indices, weights and sampling
+// keys as 8 bytes.
+const COORD_BYTES: usize = 4;
+const WORD_BYTES: usize = 8;
+
+// What one machine sends to the coordinator, and receives from it, in one round. `points` and
+// `sketch_weight` count the sketch or sample points sent.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RoundCost {
+    pub round: usize,
+    pub machine: usize,
+    pub points: usize,
+    pub sketch_weight: usize,
+    pub candidates: usize,
+    pub bytes_sent: usize,
+    pub bytes_received: usize,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CostReport(pub Vec<RoundCost>);
+
+impl CostReport {
+    pub fn bytes_sent(&self) -> usize {
+        self.0.iter().map(|c| c.bytes_sent).sum()
+    }
+
+    pub fn bytes_received(&self) -> usize {
+        self.0.iter().map(|c| c.bytes_received).sum()
+    }
+
+    pub fn bytes(&self) -> usize {
+        self.bytes_sent() + self.bytes_received()
+    }
+
+    pub fn points(&self) -> usize {
+        self.0.iter().map(|c| c.points).sum()
+    }
+
+    pub fn candidates(&self) -> usize {
+        self.0.iter().map(|c| c.candidates).sum()
+    }
+
+    pub fn n_rounds(&self) -> usize {
+        self.0.iter().map(|c| c.round + 1).max().unwrap_or(0)
+    }
+
+    pub fn machine(&self, machine: usize) -> impl Iterator<Item = &RoundCost> {
+        self.0.iter().filter(move |c| c.machine == machine)
+    }
+
+    pub fn round(&self, round: usize) -> impl Iterator<Item = &RoundCost> {
+        self.0.iter().filter(move |c| c.round == round)
+    }
+}
+
+fn point_bytes(d: usize) -> usize {
+    d * COORD_BYTES + WORD_BYTES
+}
+
+// Cost of machine `machine` sending its sketch and candidates in round `round`.
+pub(super) fn partial_cost(
+    cfg: &Config,
+    round: usize,
+    machine: usize,
+    f: &RSF,
+    candidates: &[(usize, Array1<f32>)],
+) -> RoundCost {
+    let points = f.points();
+    let n_points = points.iter().map(|t| t.len()).sum::<usize>();
+    RoundCost {
+        round,
+        machine,
+        points: n_points,
+        sketch_weight: points.iter().flatten().map(|&(_, w)| w).sum(),
+        candidates: candidates.len(),
+        bytes_sent: (n_points + candidates.len()) * point_bytes(cfg.bb.d()),
+        bytes_received: 0,
+    }
+}
+
+// Aggregation tree between the machines and the coordinator. Every aggregator merges the
+// forests of up to `fan_in` children, re-sketches the merge to `sketch_size` and passes it up
+// with the `n1` most anomalous candidates of its children against it.
+#[derive(Clone, Copy, Debug)]
+pub struct HierarchyConfig {
+    pub fan_in: usize,
+    pub sketch_size: usize,
+}
+
+impl HierarchyConfig {
+    pub fn new(fan_in: usize, sketch_size: usize) -> Self {
+        assert!(fan_in > 1, "invalid fan-in");
+        Self {
+            fan_in,
+            sketch_size,
+        }
+    }
+
+    // Number of levels of forests sent up, the machines included.
+    pub fn depth(&self, n_machines: usize) -> usize {
+        let mut depth = 1;
+        let mut n_nodes = n_machines;
+        while n_nodes > self.fan_in {
+            n_nodes = n_nodes.div_ceil(self.fan_in);
+            depth += 1;
+        }
+        depth
+    }
+}
+
+// Forest of machine `machine`, sketched, with its points reduced to its `n1` candidate anomalies.
+// The samples of the machines are drawn from different seeds derived from the config, none of
+// them the seed of `RandomRanges::from_config`.
+pub(crate) fn partial_forest(
+    cfg: &Config,
+    machine: usize,
+    points: &mut Vec<(usize, Array1<f32>)>,
+    n1: usize,
+) -> RSF {
+    let sample_size = cfg.n_points / cfg.n_machines;
+    let seed = cfg.get_rng().gen::<u64>().wrapping_add(machine as u64 + 1);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut f = RSF::from_config(cfg);
+    for tree in f.iter_trees_mut() {
+        for (_i, p) in points.choose_multiple(&mut rng, sample_size) {
+            tree.insert(p);
+        }
+    }
+    let scores: Array1<_> = points.iter().map(|(_i, p)| f.score(p)).collect();
+    retain(points, &scores, n1);
+    f.sketch(cfg.sketch_size);
+    f
+}
+
+// The `n1` most anomalous candidates against the merged forest.
+pub(super) fn top_candidates(
+    f: &RSF,
+    mut candidates: Vec<(usize, Array1<f32>)>,
+    n1: usize,
+) -> Vec<usize> {
+    let scores: Array1<_> = candidates.iter().map(|(_i, p)| f.score(p)).collect();
+    retain(&mut candidates, &scores, n1);
+    candidates.into_iter().map(|(i, _p)| i).collect()
+}
+
 pub trait DistributedIter<S: Data<Elem = f32>>: Iterator<Item = ArrayBase<S, Ix1>> + Sized {
     fn distribute_balanced(self, cfg: &Config) -> HashMap<usize, Vec<(usize, Array1<f32>)>> {
         let mut rng = thread_rng();
@@ -29,49 +178,42 @@ pub trait DistributedIter<S: Data<Elem = f32>>: Iterator<Item = ArrayBase<S, Ix1
     }
 
     fn distribute(self, cfg: &Config) -> Vec<Vec<(usize, Array1<f32>)>> {
+        self.distribute_with(cfg, &RandomRanges::from_config(cfg))
+    }
+
+    fn distribute_with<P: Partitioner>(
+        self,
+        cfg: &Config,
+        partitioner: &P,
+    ) -> Vec<Vec<(usize, Array1<f32>)>> {
         let points: Vec<_> = self.map(|p| p.to_owned()).enumerate().collect();
-        let n = points.len();
+        partitioner.partition(points, cfg.n_machines)
+    }
 
-        let mut splits: Vec<usize> = Uniform::new(0, n)
-            .sample_iter(thread_rng())
-            .take(cfg.n_machines - 1)
-            .chain([0, n])
-            .collect();
-        splits.sort_unstable();
-
-        splits
-            .windows(2)
-            .scan(points, |points, win| {
-                let (lb, ub) = (win[0], win[1]);
-                let at = points.len() - (ub - lb);
-                Some(points.split_off(at))
-            })
-            .collect()
+    fn one_way_coordinator(self, cfg: &Config, n1: usize) -> (RSF, Vec<usize>, CostReport) {
+        self.one_way_coordinator_with(cfg, n1, &RandomRanges::from_config(cfg))
     }
 
-    fn one_way_coordinator(self, cfg: &Config, n1: usize) -> (RSF, Vec<usize>) {
-        let sample_size = cfg.n_points / cfg.n_machines;
-        let distr = self.distribute(cfg);
+    fn one_way_coordinator_with<P: Partitioner>(
+        self,
+        cfg: &Config,
+        n1: usize,
+        partitioner: &P,
+    ) -> (RSF, Vec<usize>, CostReport) {
+        let distr = self.distribute_with(cfg, partitioner);
 
         // partial forests
-        let (sketches, candidates): (Vec<_>, Vec<_>) = distr
+        let (partials, costs): (Vec<_>, Vec<_>) = distr
             .into_par_iter()
-            .map(|mut points| {
-                // construct forest
-                let mut f = RSF::from_config(cfg);
-                for tree in f.iter_trees_mut() {
-                    for (_i, p) in points.choose_multiple(&mut thread_rng(), sample_size) {
-                        tree.insert(p);
-                    }
-                }
-                // score points
-                let scores: Array1<_> = points.iter().map(|(_i, p)| f.score(p)).collect();
-                retain(&mut points, &scores, n1);
-                f.sketch(cfg.sketch_size);
-                (f, points)
+            .enumerate()
+            .map(|(m, mut points)| {
+                let f = partial_forest(cfg, m, &mut points, n1);
+                let cost = partial_cost(cfg, 0, m, &f, &points);
+                ((f, points), cost)
             })
             .unzip();
-        let mut candidates: Vec<_> = candidates.into_iter().flatten().collect();
+        let (sketches, candidates): (Vec<_>, Vec<_>) = partials.into_iter().unzip();
+        let candidates: Vec<_> = candidates.into_iter().flatten().collect();
 
         // full forest
         let sketch_sum =
@@ -81,47 +223,144 @@ pub trait DistributedIter<S: Data<Elem = f32>>: Iterator<Item = ArrayBase<S, Ix1
                     sketch_sum.extend(sketch);
                     sketch_sum
                 });
-        let scores: Array1<_> = candidates
-            .iter()
-            .map(|(_i, p)| sketch_sum.score(p))
+        let anomalies = top_candidates(&sketch_sum, candidates, n1);
+        (sketch_sum, anomalies, CostReport(costs))
+    }
+
+    fn hierarchical_coordinator(
+        self,
+        cfg: &Config,
+        n1: usize,
+        hierarchy: &HierarchyConfig,
+    ) -> (RSF, Vec<usize>, CostReport) {
+        self.hierarchical_coordinator_with(cfg, n1, hierarchy, &RandomRanges::from_config(cfg))
+    }
+
+    // Same as `one_way_coordinator`, with the forests of the machines aggregated level by level
+    // and the coordinator at the root. Round `l` of the cost report is what level `l` sends up.
+    fn hierarchical_coordinator_with<P: Partitioner>(
+        self,
+        cfg: &Config,
+        n1: usize,
+        hierarchy: &HierarchyConfig,
+        partitioner: &P,
+    ) -> (RSF, Vec<usize>, CostReport) {
+        let distr = self.distribute_with(cfg, partitioner);
+
+        // partial forests
+        let mut nodes: Vec<_> = distr
+            .into_par_iter()
+            .enumerate()
+            .map(|(m, mut points)| {
+                let f = partial_forest(cfg, m, &mut points, n1);
+                (f, points)
+            })
             .collect();
-        retain(&mut candidates, &scores, n1);
-        let anomalies: Vec<_> = candidates.into_iter().map(|(i, _p)| i).collect();
-        (sketch_sum, anomalies)
+
+        // aggregators, up to the coordinator
+        let mut costs = Vec::new();
+        for level in 0.. {
+            costs.extend(
+                nodes
+                    .iter()
+                    .enumerate()
+                    .map(|(j, (f, candidates))| partial_cost(cfg, level, j, f, candidates)),
+            );
+            let is_root = nodes.len() <= hierarchy.fan_in;
+            nodes = nodes
+                .into_par_iter()
+                .chunks(hierarchy.fan_in)
+                .map(|children| {
+                    let mut merged = RSF::from_config(cfg);
+                    let mut candidates = Vec::new();
+                    for (f, child_candidates) in children {
+                        merged.extend(f);
+                        candidates.extend(child_candidates);
+                    }
+                    if !is_root {
+                        merged.sketch(hierarchy.sketch_size);
+                        let scores: Array1<_> =
+                            candidates.iter().map(|(_i, p)| merged.score(p)).collect();
+                        retain(&mut candidates, &scores, n1);
+                    }
+                    (merged, candidates)
+                })
+                .collect();
+            if is_root {
+                break;
+            }
+        }
+
+        let (root, candidates) = nodes.pop().unwrap();
+        let anomalies = top_candidates(&root, candidates, n1);
+        (root, anomalies, CostReport(costs))
+    }
+
+    fn two_way_par_streams(self, cfg: &Config, n1: usize) -> (RSF, Vec<usize>, CostReport) {
+        self.two_way_par_streams_with(cfg, n1, &RandomRanges::from_config(cfg))
     }
 
-    fn two_way_par_streams(self, cfg: &Config, n1: usize) -> (RSF, Vec<usize>) {
-        let distr = self.distribute(cfg);
+    fn two_way_par_streams_with<P: Partitioner>(
+        self,
+        cfg: &Config,
+        n1: usize,
+        partitioner: &P,
+    ) -> (RSF, Vec<usize>, CostReport) {
+        let distr = self.distribute_with(cfg, partitioner);
+        let d = cfg.bb.d();
+        let mut costs = Vec::new();
 
-        // pass 1
+        // pass 1, where every point sent is answered with a threshold
         let mut sampler = ParStreamSampler::new(cfg);
         for (m, points) in distr.iter().enumerate() {
-            for (_p, point) in points {
-                sampler.insert(m, point);
-            }
+            let n_sent = points
+                .iter()
+                .filter(|(_p, point)| sampler.insert(m, point))
+                .count();
+            costs.push(RoundCost {
+                round: 0,
+                machine: m,
+                points: n_sent,
+                sketch_weight: n_sent,
+                candidates: 0,
+                bytes_sent: n_sent * point_bytes(d),
+                bytes_received: n_sent * WORD_BYTES,
+            });
         }
         let sample = sampler.query(cfg);
         let mut f = RSF::from_config(cfg);
         f.batch_insert(&sample);
         f.sketch(cfg.sketch_size);
 
-        // pass 2
-        let mut candidates: Vec<_> = distr
+        // pass 2, where the sketch is broadcast and the machines send back their top scores
+        let sketch_bytes = partial_cost(cfg, 1, 0, &f, &[]).bytes_sent;
+        let (candidates, pass_costs): (Vec<_>, Vec<_>) = distr
             .into_par_iter()
-            .flat_map(|points| {
+            .enumerate()
+            .map(|(m, points)| {
                 let mut scores = points
                     .into_iter()
                     .map(|(i, p)| (i, f.score(&p)))
                     .collect::<Vec<_>>();
-                scores.sort_unstable_by(|a, b| a.1.total_cmp(&b.1));
+                scores.sort_unstable_by_key(|c| Reverse(c.1));
                 scores.truncate(n1);
-                scores
+                let cost = RoundCost {
+                    round: 1,
+                    machine: m,
+                    candidates: scores.len(),
+                    bytes_sent: scores.len() * (WORD_BYTES + COORD_BYTES),
+                    bytes_received: sketch_bytes,
+                    ..Default::default()
+                };
+                (scores, cost)
             })
-            .collect();
-        candidates.sort_unstable_by(|a, b| a.1.total_cmp(&b.1));
+            .unzip();
+        costs.extend(pass_costs);
+        let mut candidates: Vec<_> = candidates.into_iter().flatten().collect();
+        candidates.sort_unstable_by_key(|c| Reverse(c.1));
         let anomalies = candidates.into_iter().take(n1).map(|c| c.0).collect();
 
-        (f, anomalies)
+        (f, anomalies, CostReport(costs))
     }
 }
 
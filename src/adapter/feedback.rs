@@ -0,0 +1,62 @@
+use std::collections::VecDeque;
+
+use ndarray::{Array1, ArrayBase, Data, Ix1};
+
+use crate::algorithm::forest::{MAX_DAMPED, RSF};
+
+// Analyst feedback held by a streaming detector on top of its sample, until `horizon` ticks of the
+// detector's clock (points, or time units) have passed. Points labelled as normal are kept apart
+// from the sample, so they never push real points out of it.
+pub struct Feedback {
+    horizon: u64,
+    damped_at: VecDeque<u64>,
+    normals: VecDeque<(u64, Array1<f32>, usize)>,
+}
+
+impl Feedback {
+    pub fn new(horizon: u64) -> Self {
+        Self {
+            horizon,
+            damped_at: VecDeque::new(),
+            normals: VecDeque::new(),
+        }
+    }
+
+    pub fn damp<S: Data<Elem = f32>>(
+        &mut self,
+        f: &mut RSF,
+        p: &ArrayBase<S, Ix1>,
+        factor: f32,
+        now: u64,
+    ) {
+        f.damp(p, factor);
+        self.damped_at.push_back(now);
+        if self.damped_at.len() > MAX_DAMPED {
+            self.damped_at.pop_front();
+        }
+    }
+
+    pub fn insert_normal<S: Data<Elem = f32>>(
+        &mut self,
+        f: &mut RSF,
+        p: &ArrayBase<S, Ix1>,
+        weight: usize,
+        now: u64,
+    ) {
+        f.insert_weighted(p, weight);
+        self.normals.push_back((now, p.to_owned(), weight));
+    }
+
+    // Takes the feedback older than the horizon out of the forest.
+    pub fn expire(&mut self, f: &mut RSF, now: u64) {
+        let expired = |at: u64| at.saturating_add(self.horizon) <= now;
+        while self.damped_at.front().is_some_and(|&at| expired(at)) {
+            self.damped_at.pop_front();
+            f.expire_damped();
+        }
+        while self.normals.front().is_some_and(|&(at, _, _)| expired(at)) {
+            let (_, p, weight) = self.normals.pop_front().unwrap();
+            (0..weight).for_each(|_| f.remove(&p));
+        }
+    }
+}
@@ -0,0 +1,235 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    hash::Hash,
+};
+
+use ndarray::{concatenate, Array1, Axis};
+use rand::{prelude::StdRng, SeedableRng};
+
+use super::{
+    hash_picker::HashPicker,
+    spotlight::{Graph, SpotLight, SpotLightConfig},
+};
+
+// Ways of turning a graph into a vector of fixed size, so that the featuriser can be swapped
+// without touching the rest of the pipeline.
+//
+// - `SpotLight`: the SpotLight sketch, of size `k`.
+// - `Distribution(bins)`: histograms of the out-degrees, in-degrees and edge weights over
+//   power-of-two bins, the last bin taking everything above, of size `3 * bins`.
+// - `CountMin(width, depth, seed)`: count-min sketches of the out-weight of every source and
+//   the in-weight of every destination, of size `2 * width * depth`.
+// - `Densest`: density and size of the densest subgraph found by greedy peeling, next to the
+//   density of the whole graph, of size 3.
+#[derive(Clone)]
+pub enum GraphFeatures {
+    SpotLight(SpotLightConfig),
+    Distribution(usize),
+    CountMin(usize, usize, u64),
+    Densest,
+}
+
+impl GraphFeatures {
+    pub fn dim(&self) -> usize {
+        match self {
+            GraphFeatures::SpotLight(cfg) => cfg.k,
+            GraphFeatures::Distribution(bins) => 3 * bins,
+            GraphFeatures::CountMin(width, depth, _) => 2 * width * depth,
+            GraphFeatures::Densest => 3,
+        }
+    }
+}
+
+enum Featuriser {
+    SpotLight(SpotLight<()>),
+    Distribution(usize),
+    CountMin {
+        width: usize,
+        src_rows: Vec<HashPicker>,
+        dst_rows: Vec<HashPicker>,
+    },
+    Densest,
+}
+
+fn bin(x: f32, bins: usize) -> usize {
+    if x < 2. {
+        0
+    } else {
+        (x.log2().floor() as usize).min(bins - 1)
+    }
+}
+
+fn histogram(values: impl Iterator<Item = f32>, bins: usize) -> Array1<f32> {
+    let mut h = Array1::zeros(bins);
+    values.for_each(|x| h[bin(x, bins)] += 1.);
+    h
+}
+
+fn distribution<S: Hash + Eq, D: Hash + Eq>(g: &Graph<S, D>, bins: usize) -> Array1<f32> {
+    let mut out_degrees: HashMap<&S, f32> = HashMap::new();
+    let mut in_degrees: HashMap<&D, f32> = HashMap::new();
+    for (src, dst, _) in g.edge_iter() {
+        *out_degrees.entry(src).or_default() += 1.;
+        *in_degrees.entry(dst).or_default() += 1.;
+    }
+    concatenate![
+        Axis(0),
+        histogram(out_degrees.into_values(), bins),
+        histogram(in_degrees.into_values(), bins),
+        histogram(g.edge_iter().map(|&(_, _, w)| w), bins)
+    ]
+}
+
+fn count_min<T: Hash>(
+    rows: &[HashPicker],
+    width: usize,
+    weights: impl Iterator<Item = (T, f32)>,
+) -> Array1<f32> {
+    let mut sketch = Array1::zeros(rows.len() * width);
+    for (node, w) in weights {
+        for (r, row) in rows.iter().enumerate() {
+            sketch[r * width + row.bucket(&node)] += w;
+        }
+    }
+    sketch
+}
+
+// Charikar's greedy peeling on the undirected graph with sources and destinations as distinct
+// nodes: the node of least weighted degree is removed until none are left, and the densest
+// intermediate subgraph is kept.
+fn densest<S: Hash + Eq, D: Hash + Eq>(g: &Graph<S, D>) -> Array1<f32> {
+    let mut ids_src: HashMap<&S, usize> = HashMap::new();
+    let mut ids_dst: HashMap<&D, usize> = HashMap::new();
+    let mut adjacent: Vec<Vec<(usize, f32)>> = Vec::new();
+    for (src, dst, w) in g.edge_iter() {
+        assert!(*w >= 0., "negative edge weight");
+        let i = *ids_src.entry(src).or_insert_with(|| {
+            adjacent.push(Vec::new());
+            adjacent.len() - 1
+        });
+        let j = *ids_dst.entry(dst).or_insert_with(|| {
+            adjacent.push(Vec::new());
+            adjacent.len() - 1
+        });
+        adjacent[i].push((j, *w));
+        adjacent[j].push((i, *w));
+    }
+
+    let n = adjacent.len();
+    if n == 0 {
+        return Array1::zeros(3);
+    }
+    let mut degrees: Vec<f32> = adjacent
+        .iter()
+        .map(|a| a.iter().map(|&(_, w)| w).sum())
+        .collect();
+    let mut total = degrees.iter().sum::<f32>() / 2.;
+    // degrees are non-negative, so their bits are ordered like them
+    let mut queue: BTreeSet<(u32, usize)> = degrees
+        .iter()
+        .enumerate()
+        .map(|(i, d)| (d.to_bits(), i))
+        .collect();
+    let mut removed = vec![false; n];
+    let density = total / (n as f32);
+    let (mut best, mut best_size) = (density, n);
+    for size in (1..n).rev() {
+        let (_, i) = queue.pop_first().unwrap();
+        removed[i] = true;
+        total -= degrees[i];
+        for &(j, w) in &adjacent[i] {
+            if !removed[j] {
+                queue.remove(&(degrees[j].to_bits(), j));
+                degrees[j] = (degrees[j] - w).max(0.);
+                queue.insert((degrees[j].to_bits(), j));
+            }
+        }
+        if total / (size as f32) > best {
+            best = total / (size as f32);
+            best_size = size;
+        }
+    }
+    Array1::from(vec![best, best_size as f32, density])
+}
+
+pub struct Featurise<I> {
+    iter: I,
+    featuriser: Featuriser,
+}
+
+impl<I> Featurise<I> {
+    fn new(iter: I, features: &GraphFeatures) -> Self {
+        let featuriser = match features {
+            GraphFeatures::SpotLight(cfg) => Featuriser::SpotLight(SpotLight::new((), cfg)),
+            &GraphFeatures::Distribution(bins) => {
+                assert!(bins > 0, "invalid number of bins");
+                Featuriser::Distribution(bins)
+            }
+            &GraphFeatures::CountMin(width, depth, seed) => {
+                assert!(width > 0 && depth > 0, "invalid count-min size");
+                let mut rng = StdRng::seed_from_u64(seed);
+                let mut rows = || {
+                    (0..depth)
+                        .map(|_| HashPicker::from_frac(1, width, &mut rng))
+                        .collect()
+                };
+                Featuriser::CountMin {
+                    width,
+                    src_rows: rows(),
+                    dst_rows: rows(),
+                }
+            }
+            GraphFeatures::Densest => Featuriser::Densest,
+        };
+        Self { iter, featuriser }
+    }
+}
+
+impl<S, D, I> Iterator for Featurise<I>
+where
+    S: Hash + Eq,
+    D: Hash + Eq,
+    I: Iterator<Item = Graph<S, D>>,
+{
+    type Item = Array1<f32>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let g = self.iter.next()?;
+        Some(match &self.featuriser {
+            Featuriser::SpotLight(spotlight) => spotlight.sketch(g),
+            &Featuriser::Distribution(bins) => distribution(&g, bins),
+            Featuriser::CountMin {
+                width,
+                src_rows,
+                dst_rows,
+            } => concatenate![
+                Axis(0),
+                count_min(src_rows, *width, g.edge_iter().map(|(s, _, w)| (s, *w))),
+                count_min(dst_rows, *width, g.edge_iter().map(|(_, d, w)| (d, *w)))
+            ],
+            Featuriser::Densest => densest(&g),
+        })
+    }
+}
+
+pub trait GraphFeaturesIter<S: Hash + Eq, D: Hash + Eq>:
+    Iterator<Item = Graph<S, D>> + Sized
+{
+    fn featurise(self, features: &GraphFeatures) -> Featurise<Self> {
+        Featurise::new(self, features)
+    }
+
+    fn degree_distribution(self, bins: usize) -> Featurise<Self> {
+        self.featurise(&GraphFeatures::Distribution(bins))
+    }
+
+    fn count_min(self, width: usize, depth: usize, seed: u64) -> Featurise<Self> {
+        self.featurise(&GraphFeatures::CountMin(width, depth, seed))
+    }
+
+    fn densest_subgraph(self) -> Featurise<Self> {
+        self.featurise(&GraphFeatures::Densest)
+    }
+}
+
+impl<S: Hash + Eq, D: Hash + Eq, I: Iterator<Item = Graph<S, D>>> GraphFeaturesIter<S, D> for I {}
@@ -1,34 +1,97 @@
-use std::{
-    collections::hash_map::RandomState,
-    hash::{BuildHasher, Hash, Hasher},
-};
+use std::hash::{Hash, Hasher};
 
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use siphasher::sip::SipHasher13;
+
+// Picks items by a keyed SipHash, so that pickers with the same keys pick the same items across
+// restarts and machines.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct HashPicker {
-    s: RandomState,
+    keys: (u64, u64),
     num: u64,
     den: u64,
 }
 
 impl HashPicker {
-    pub fn from_frac(num: usize, den: usize) -> Self {
+    pub fn from_frac<R: Rng>(num: usize, den: usize, rng: &mut R) -> Self {
         assert!(num <= den, "invalid picking fraction");
         Self {
-            s: RandomState::new(),
+            keys: rng.gen(),
             num: num as u64,
             den: den as u64,
         }
     }
 
-    pub fn from_prob(p: f64) -> Self {
+    pub fn from_prob<R: Rng>(p: f64, rng: &mut R) -> Self {
         let num = 1;
         let den = p.recip().floor() as usize;
-        Self::from_frac(num, den)
+        Self::from_frac(num, den, rng)
     }
 
-    pub fn picks<I: Hash>(&self, i: &I) -> bool {
-        let mut hasher = self.s.build_hasher();
+    // Bucket of `i` among `den` buckets; the picked items are those in the first `num`.
+    pub fn bucket<I: Hash>(&self, i: &I) -> usize {
+        let mut hasher = Portable(SipHasher13::new_with_keys(self.keys.0, self.keys.1));
         i.hash(&mut hasher);
         let h = hasher.finish();
-        (h % self.den) < self.num
+        (h % self.den) as usize
+    }
+
+    pub fn picks<I: Hash>(&self, i: &I) -> bool {
+        (self.bucket(i) as u64) < self.num
+    }
+}
+
+// Feeds integers to the hasher as little-endian bytes, and `usize` and `isize` as 64 bits, so that
+// the buckets do not depend on the endianness and pointer width of the machine.
+struct Portable<H>(H);
+
+impl<H: Hasher> Hasher for Portable<H> {
+    fn finish(&self) -> u64 {
+        self.0.finish()
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.write(bytes)
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.0.write(&i.to_le_bytes())
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.0.write(&i.to_le_bytes())
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.0.write(&i.to_le_bytes())
+    }
+
+    fn write_u128(&mut self, i: u128) {
+        self.0.write(&i.to_le_bytes())
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.0.write(&(i as u64).to_le_bytes())
+    }
+
+    fn write_i16(&mut self, i: i16) {
+        self.0.write(&i.to_le_bytes())
+    }
+
+    fn write_i32(&mut self, i: i32) {
+        self.0.write(&i.to_le_bytes())
+    }
+
+    fn write_i64(&mut self, i: i64) {
+        self.0.write(&i.to_le_bytes())
+    }
+
+    fn write_i128(&mut self, i: i128) {
+        self.0.write(&i.to_le_bytes())
+    }
+
+    fn write_isize(&mut self, i: isize) {
+        self.0.write(&(i as i64).to_le_bytes())
     }
 }
@@ -0,0 +1,175 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+};
+
+use ndarray::{Array1, ArrayBase, Data, Ix1};
+
+use crate::{
+    algorithm::{config::Config, forest::RSF},
+    score::PathLength,
+};
+
+use super::rsf_window::WindowDetector;
+
+#[derive(Clone, Default)]
+pub struct KeyedConfig {
+    pub idle: Option<usize>,
+    pub max_keys: Option<usize>,
+    pub max_points: Option<usize>,
+}
+
+impl KeyedConfig {
+    // Keys not seen during the last `idle` items are dropped.
+    pub fn idle(mut self, idle: usize) -> Self {
+        assert!(idle > 0, "invalid idle time");
+        self.idle = Some(idle);
+        self
+    }
+
+    // At most `max_keys` forests are kept, the least recently seen key is dropped first.
+    pub fn max_keys(mut self, max_keys: usize) -> Self {
+        assert!(max_keys > 0, "invalid number of keys");
+        self.max_keys = Some(max_keys);
+        self
+    }
+
+    // Memory cap in points: every key holds its window and `n_trees` samples of `n_points`, so
+    // at most `max_points / (window + n_trees * n_points)` keys are kept, least recently seen
+    // dropped first.
+    pub fn max_points(mut self, max_points: usize) -> Self {
+        assert!(max_points > 0, "invalid number of points");
+        self.max_points = Some(max_points);
+        self
+    }
+
+    fn key_cap(&self, cfg: &Config) -> Option<usize> {
+        let by_points = self.max_points.map(|max_points| {
+            let per_key = cfg.window + cfg.n_trees * cfg.n_points;
+            assert!(max_points >= per_key, "memory cap below a single key");
+            max_points / per_key
+        });
+        match (self.max_keys, by_points) {
+            (Some(k1), Some(k2)) => Some(k1.min(k2)),
+            (k1, k2) => k1.or(k2),
+        }
+    }
+}
+
+struct Entity<const M: bool> {
+    d: WindowDetector<M>,
+    last_seen: usize,
+}
+
+pub struct Keyed<I, K, const M: bool> {
+    iter: I,
+    cfg: Config,
+    keyed_cfg: KeyedConfig,
+    max_keys: Option<usize>,
+    entities: HashMap<K, Entity<M>>,
+    by_last_seen: BTreeMap<usize, K>,
+    now: usize,
+}
+
+impl<S, I, K, const M: bool> Keyed<I, K, M>
+where
+    S: Data<Elem = f32>,
+    I: Iterator<Item = (K, ArrayBase<S, Ix1>)>,
+    K: Hash + Eq + Clone,
+{
+    fn new(iter: I, cfg: &Config, keyed_cfg: &KeyedConfig) -> Self {
+        Self {
+            iter,
+            cfg: cfg.clone(),
+            keyed_cfg: keyed_cfg.clone(),
+            max_keys: keyed_cfg.key_cap(cfg),
+            entities: HashMap::new(),
+            by_last_seen: BTreeMap::new(),
+            now: 0,
+        }
+    }
+
+    pub fn forest(&self, key: &K) -> Option<&RSF> {
+        self.entities.get(key).map(|e| e.d.forest())
+    }
+
+    pub fn n_keys(&self) -> usize {
+        self.entities.len()
+    }
+
+    fn evict_oldest(&mut self) {
+        if let Some((_t, key)) = self.by_last_seen.pop_first() {
+            self.entities.remove(&key);
+        }
+    }
+
+    fn evict(&mut self) {
+        if let Some(idle) = self.keyed_cfg.idle {
+            while let Some((&t, _key)) = self.by_last_seen.first_key_value() {
+                if t + idle >= self.now {
+                    break;
+                }
+                self.evict_oldest();
+            }
+        }
+    }
+
+    fn update(&mut self, key: K, p: Array1<f32>) -> Option<PathLength> {
+        self.now += 1;
+        self.evict();
+        let now = self.now;
+        if let Some(e) = self.entities.get_mut(&key) {
+            self.by_last_seen.remove(&e.last_seen);
+            e.last_seen = now;
+        } else {
+            if let Some(max_keys) = self.max_keys {
+                while self.entities.len() >= max_keys {
+                    self.evict_oldest();
+                }
+            }
+            let d = WindowDetector::new(&self.cfg);
+            self.entities
+                .insert(key.clone(), Entity { d, last_seen: now });
+        }
+        self.by_last_seen.insert(now, key.clone());
+        self.entities.get_mut(&key).unwrap().d.update(&p)
+    }
+}
+
+impl<S, I, K, const M: bool> Iterator for Keyed<I, K, M>
+where
+    S: Data<Elem = f32>,
+    I: Iterator<Item = (K, ArrayBase<S, Ix1>)>,
+    K: Hash + Eq + Clone,
+{
+    type Item = (K, PathLength);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (key, p) = self.iter.next()?;
+            if let Some(s) = self.update(key.clone(), p.to_owned()) {
+                return Some((key, s));
+            }
+        }
+    }
+}
+
+pub trait KeyedIter<S: Data<Elem = f32>, K: Hash + Eq + Clone>:
+    Iterator<Item = (K, ArrayBase<S, Ix1>)> + Sized
+{
+    // Routes every point to the forest of its key, created from `cfg` on first sight. As with
+    // `rsf_window`, a key is only scored once its window is full. Every key holds a whole
+    // detector, so without `max_keys`, `max_points` or `idle` memory grows with the number of
+    // keys.
+    fn keyed<const M: bool>(self, cfg: &Config, keyed_cfg: &KeyedConfig) -> Keyed<Self, K, M> {
+        Keyed::new(self, cfg, keyed_cfg)
+    }
+}
+
+impl<S, K, I> KeyedIter<S, K> for I
+where
+    S: Data<Elem = f32>,
+    K: Hash + Eq + Clone,
+    I: Iterator<Item = (K, ArrayBase<S, Ix1>)>,
+{
+}
@@ -1,13 +1,25 @@
+pub mod aligned;
+pub mod calibrate;
 pub mod distributed;
-mod hash_picker;
+mod feedback;
+pub mod graph_features;
+pub mod hash_picker;
+pub mod keyed;
+pub mod monitor;
 pub mod normalise;
+pub mod online_normalise;
 mod par_stream_sampler;
+pub mod partition;
 pub mod prelude;
+pub mod remote;
 mod reservoir;
 pub mod rsf_reservoir;
 pub mod rsf_split;
+pub mod rsf_time_window;
 pub mod rsf_window;
 pub mod shingle;
 pub mod spotlight;
+#[cfg(feature = "stream")]
+pub mod stream;
+pub mod threshold;
 pub mod transform;
-mod window;
@@ -0,0 +1,209 @@
+use std::{collections::VecDeque, rc::Rc};
+
+use ndarray::{Array1, ArrayBase, Data, Ix1};
+
+use super::{
+    distributed::{partial_cost, CostReport},
+    rsf_reservoir::ReservoirDetector,
+    rsf_window::WindowDetector,
+};
+use crate::{
+    algorithm::{config::Config, forest::RSF},
+    score::PathLength,
+};
+
+// Local model of a monitoring site.
+pub trait SiteDetector {
+    fn new(cfg: &Config) -> Self;
+    fn forest(&self) -> &RSF;
+    fn update<S: Data<Elem = f32>>(&mut self, p: &ArrayBase<S, Ix1>) -> Option<PathLength>;
+}
+
+impl<const M: bool> SiteDetector for WindowDetector<M> {
+    fn new(cfg: &Config) -> Self {
+        WindowDetector::new(cfg)
+    }
+
+    fn forest(&self) -> &RSF {
+        self.forest()
+    }
+
+    fn update<S: Data<Elem = f32>>(&mut self, p: &ArrayBase<S, Ix1>) -> Option<PathLength> {
+        self.update(p)
+    }
+}
+
+impl<const M: bool> SiteDetector for ReservoirDetector<M> {
+    fn new(cfg: &Config) -> Self {
+        ReservoirDetector::new(cfg)
+    }
+
+    fn forest(&self) -> &RSF {
+        self.forest()
+    }
+
+    fn update<S: Data<Elem = f32>>(&mut self, p: &ArrayBase<S, Ix1>) -> Option<PathLength> {
+        self.update(p)
+    }
+}
+
+// Every `sync_interval` of its points, a site ships the sketch of its forest to the coordinator,
+// which replies with the merge of the latest sketches of all sites. The reply reaches the site
+// `staleness` points later.
+//
+// The global model holds the weight of every site that has synced, so its path lengths are longer
+// than those of a local model and grow as sites join. Only scores against the global model are
+// emitted, the two are never mixed.
+#[derive(Clone, Copy, Debug)]
+pub struct MonitorConfig {
+    pub sync_interval: usize,
+    pub staleness: usize,
+}
+
+impl MonitorConfig {
+    pub fn new(sync_interval: usize) -> Self {
+        assert!(sync_interval > 0, "invalid sync interval");
+        Self {
+            sync_interval,
+            staleness: 0,
+        }
+    }
+
+    pub fn staleness(mut self, staleness: usize) -> Self {
+        self.staleness = staleness;
+        self
+    }
+}
+
+type ForestPoints = Vec<Vec<(Array1<f32>, usize)>>;
+
+struct Site<D> {
+    d: D,
+    n: usize,
+    to_sync: usize,
+    global: Option<Rc<RSF>>,
+    pending: VecDeque<(usize, Rc<RSF>)>,
+}
+
+pub struct Monitor<I, D> {
+    iter: I,
+    cfg: Config,
+    monitor_cfg: MonitorConfig,
+    sites: Vec<Site<D>>,
+    global: RSF,
+    // Points each site last contributed to the global model.
+    contributions: Vec<Option<ForestPoints>>,
+    n_syncs: Vec<usize>,
+    cost: CostReport,
+}
+
+impl<I, D: SiteDetector> Monitor<I, D> {
+    fn new(iter: I, cfg: &Config, monitor_cfg: &MonitorConfig) -> Self {
+        let sites = (0..cfg.n_machines)
+            .map(|_| Site {
+                d: D::new(cfg),
+                n: 0,
+                to_sync: monitor_cfg.sync_interval,
+                global: None,
+                pending: VecDeque::new(),
+            })
+            .collect();
+        Self {
+            iter,
+            cfg: cfg.clone(),
+            monitor_cfg: *monitor_cfg,
+            sites,
+            global: RSF::from_config(cfg),
+            contributions: vec![None; cfg.n_machines],
+            n_syncs: vec![0; cfg.n_machines],
+            cost: CostReport::default(),
+        }
+    }
+
+    // Global model as currently known by `site`.
+    pub fn global(&self, site: usize) -> Option<&RSF> {
+        self.sites[site].global.as_deref()
+    }
+
+    pub fn site(&self, site: usize) -> &D {
+        &self.sites[site].d
+    }
+
+    pub fn cost(&self) -> &CostReport {
+        &self.cost
+    }
+
+    fn sync(&mut self, m: usize) {
+        let mut sketch = self.sites[m].d.forest().clone();
+        sketch.sketch(self.cfg.sketch_size);
+        let round = self.n_syncs[m];
+        let mut cost = partial_cost(&self.cfg, round, m, &sketch, &[]);
+
+        // Only the contribution of site `m` changes; feedback damping stays local to the sites.
+        let points = sketch.points();
+        if let Some(old) = self.contributions[m].replace(points.clone()) {
+            self.global.remove_points(&old);
+        }
+        self.global.insert_points(&points);
+        cost.bytes_received = partial_cost(&self.cfg, round, m, &self.global, &[]).bytes_sent;
+        self.cost.0.push(cost);
+        self.n_syncs[m] += 1;
+
+        let site = &mut self.sites[m];
+        let at = site.n + self.monitor_cfg.staleness;
+        site.pending.push_back((at, Rc::new(self.global.clone())));
+    }
+}
+
+impl<S, I, D> Iterator for Monitor<I, D>
+where
+    S: Data<Elem = f32>,
+    I: Iterator<Item = (usize, ArrayBase<S, Ix1>)>,
+    D: SiteDetector,
+{
+    type Item = (usize, PathLength);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (m, p) = self.iter.next()?;
+            assert!(m < self.sites.len(), "unknown site");
+            let site = &mut self.sites[m];
+            while site.pending.front().is_some_and(|&(at, _)| at <= site.n) {
+                site.global = site.pending.pop_front().map(|(_, global)| global);
+            }
+            site.d.update(&p);
+            let s = site.global.as_ref().map(|global| global.score(&p));
+            site.n += 1;
+            site.to_sync -= 1;
+            if site.to_sync == 0 {
+                site.to_sync = self.monitor_cfg.sync_interval;
+                self.sync(m);
+            }
+            if let Some(s) = s {
+                return Some((m, s));
+            }
+        }
+    }
+}
+
+pub trait MonitorIter<S: Data<Elem = f32>>:
+    Iterator<Item = (usize, ArrayBase<S, Ix1>)> + Sized
+{
+    // Monitors the points of `cfg.n_machines` sites, given with the site they come from. Points
+    // are scored against the global model known by their site; the points a site sees before its
+    // first reply only update its own model.
+    fn monitor<D: SiteDetector>(
+        self,
+        cfg: &Config,
+        monitor_cfg: &MonitorConfig,
+    ) -> Monitor<Self, D> {
+        Monitor::new(self, cfg, monitor_cfg)
+    }
+}
+
+impl<S, I> MonitorIter<S> for I
+where
+    S: Data<Elem = f32>,
+    I: Iterator<Item = (usize, ArrayBase<S, Ix1>)>,
+{
+}
@@ -0,0 +1,305 @@
+use std::collections::VecDeque;
+
+use ndarray::{stack, Array1, ArrayBase, Axis, Data, Ix1, Zip};
+
+use crate::algorithm::bounding_box::{BoundingBox, BoundingBoxIter};
+
+#[derive(Clone, Copy, Debug)]
+pub enum Scaling {
+    MinMax,
+    ZScore,
+    Quantile(f32, f32),
+}
+
+#[derive(Clone)]
+pub struct OnlineNormaliseConfig {
+    pub scaling: Scaling,
+    pub warm_up: usize,
+    pub margin: f32,
+}
+
+impl OnlineNormaliseConfig {
+    pub fn new(scaling: Scaling, warm_up: usize) -> Self {
+        assert!(warm_up > 0, "invalid warm-up size");
+        if let Scaling::Quantile(lo, hi) = scaling {
+            assert!(0. <= lo && lo < hi && hi <= 1., "invalid quantiles");
+        }
+        Self {
+            scaling,
+            warm_up,
+            margin: 0.1,
+        }
+    }
+
+    pub fn margin(mut self, margin: f32) -> Self {
+        assert!(margin >= 0., "invalid margin");
+        self.margin = margin;
+        self
+    }
+}
+
+// Per dimension running statistics from which the normalisation is derived.
+struct Stats {
+    scaling: Scaling,
+    n: usize,
+    min: Array1<f32>,
+    max: Array1<f32>,
+    mean: Array1<f32>,
+    m2: Array1<f32>,
+    lo: Array1<f32>,
+    hi: Array1<f32>,
+    lr: f32,
+}
+
+impl Stats {
+    fn new(warm_up: &[Array1<f32>], scaling: Scaling) -> Self {
+        let d = warm_up[0].len();
+        let bb = warm_up.iter().map(|p| p.view()).bb().unwrap();
+        let mut stats = Self {
+            scaling,
+            n: 0,
+            min: bb.bounds.column(0).to_owned(),
+            max: bb.bounds.column(1).to_owned(),
+            mean: Array1::zeros(d),
+            m2: Array1::zeros(d),
+            lo: Array1::zeros(d),
+            hi: Array1::zeros(d),
+            lr: (warm_up.len() as f32).recip(),
+        };
+        warm_up.iter().for_each(|p| stats.update(p));
+        // exact quantiles of the warm-up, which the SGD then follows
+        if let Scaling::Quantile(q_lo, q_hi) = scaling {
+            for i in 0..d {
+                let mut vals: Vec<f32> = warm_up.iter().map(|p| p[i]).collect();
+                vals.sort_unstable_by(f32::total_cmp);
+                let at = |q: f32| vals[((vals.len() - 1) as f32 * q).round() as usize];
+                stats.lo[i] = at(q_lo);
+                stats.hi[i] = at(q_hi);
+            }
+        }
+        stats
+    }
+
+    fn update<S: Data<Elem = f32>>(&mut self, p: &ArrayBase<S, Ix1>) {
+        self.n += 1;
+        let n = self.n as f32;
+        Zip::from(&mut self.min)
+            .and(&mut self.max)
+            .and(p)
+            .for_each(|min, max, &v| {
+                *min = min.min(v);
+                *max = max.max(v);
+            });
+        Zip::from(&mut self.mean)
+            .and(&mut self.m2)
+            .and(p)
+            .for_each(|mean, m2, &v| {
+                let delta = v - *mean;
+                *mean += delta / n;
+                *m2 += delta * (v - *mean);
+            });
+        if let Scaling::Quantile(q_lo, q_hi) = self.scaling {
+            let lr = self.lr;
+            Zip::from(&mut self.lo)
+                .and(&mut self.hi)
+                .and(p)
+                .for_each(|lo, hi, &v| {
+                    let step = lr * (*hi - *lo).max(f32::EPSILON);
+                    *lo += step * (q_lo - if v < *lo { 1. } else { 0. });
+                    *hi += step * (q_hi - if v < *hi { 1. } else { 0. });
+                });
+        }
+    }
+
+    // (center, scale) of the affine normalisation
+    fn params(&self) -> (Array1<f32>, Array1<f32>) {
+        match self.scaling {
+            Scaling::MinMax => (self.min.clone(), &self.max - &self.min),
+            Scaling::ZScore => {
+                let var = &self.m2 / (self.n as f32);
+                (self.mean.clone(), var.mapv(f32::sqrt))
+            }
+            Scaling::Quantile(_, _) => (self.lo.clone(), &self.hi - &self.lo),
+        }
+    }
+}
+
+struct Transform {
+    center: Array1<f32>,
+    scale: Array1<f32>,
+    bb: BoundingBox,
+    raw_bb: BoundingBox,
+}
+
+impl Transform {
+    fn new(stats: &Stats, margin: f32) -> Self {
+        let (center, scale) = stats.params();
+        let raw_pad = (&stats.max - &stats.min) * margin;
+        let raw_bb = BoundingBox::new(stack![
+            Axis(1),
+            &stats.min - &raw_pad,
+            &stats.max + &raw_pad
+        ]);
+        let mut t = Self {
+            center,
+            scale,
+            bb: BoundingBox::unit(stats.min.len()),
+            raw_bb,
+        };
+        let lb = t.apply_unclamped(&stats.min);
+        let ub = t.apply_unclamped(&stats.max);
+        let pad = (&ub - &lb).mapv(|r| if r == 0. { margin } else { r * margin });
+        t.bb = BoundingBox::new(stack![Axis(1), &lb - &pad, &ub + &pad]);
+        t
+    }
+
+    fn apply_unclamped<S: Data<Elem = f32>>(&self, p: &ArrayBase<S, Ix1>) -> Array1<f32> {
+        let mut q = p - &self.center;
+        Zip::from(&mut q).and(&self.scale).for_each(|v, &s| {
+            if s != 0. {
+                *v /= s
+            }
+        });
+        q
+    }
+
+    fn apply<S: Data<Elem = f32>>(&self, p: &ArrayBase<S, Ix1>) -> Array1<f32> {
+        let mut q = self.apply_unclamped(p);
+        Zip::from(&mut q)
+            .and(self.bb.bounds.outer_iter())
+            .for_each(|v, bound| *v = v.clamp(bound[0], bound[1]));
+        q
+    }
+}
+
+// Nothing is emitted, and there is no frame, for an empty stream.
+pub struct OnlineNormalise<I> {
+    iter: I,
+    frame: Option<(Stats, Transform)>,
+    margin: f32,
+    buf: VecDeque<Array1<f32>>,
+}
+
+impl<S, I> OnlineNormalise<I>
+where
+    S: Data<Elem = f32>,
+    I: Iterator<Item = ArrayBase<S, Ix1>>,
+{
+    fn new(mut iter: I, cfg: &OnlineNormaliseConfig) -> Self {
+        let warm_up: Vec<_> = iter
+            .by_ref()
+            .take(cfg.warm_up)
+            .map(|p| p.to_owned())
+            .collect();
+        let frame = (!warm_up.is_empty()).then(|| {
+            let stats = Stats::new(&warm_up, cfg.scaling);
+            let t = Transform::new(&stats, cfg.margin);
+            (stats, t)
+        });
+        Self {
+            iter,
+            frame,
+            margin: cfg.margin,
+            buf: warm_up.into(),
+        }
+    }
+
+    // Bounding box of the normalised points, for building the downstream `Config`.
+    pub fn bounding_box(&self) -> Option<BoundingBox> {
+        self.frame.as_ref().map(|(_, t)| t.bb.clone())
+    }
+
+    // Raw points inside this box are normalised without clamping.
+    pub fn raw_bounding_box(&self) -> Option<BoundingBox> {
+        self.frame.as_ref().map(|(_, t)| t.raw_bb.clone())
+    }
+
+    // The statistics only need to follow the stream if they can be used again, by a rescale.
+    fn next_raw(&mut self, update: bool) -> Option<Array1<f32>> {
+        if let Some(p) = self.buf.pop_front() {
+            return Some(p);
+        }
+        let (stats, _) = self.frame.as_mut()?;
+        self.iter.next().map(|p| {
+            if update {
+                stats.update(&p);
+            }
+            p.to_owned()
+        })
+    }
+}
+
+impl<S, I> Iterator for OnlineNormalise<I>
+where
+    S: Data<Elem = f32>,
+    I: Iterator<Item = ArrayBase<S, Ix1>>,
+{
+    type Item = Array1<f32>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let p = self.next_raw(false)?;
+        self.frame.as_ref().map(|(_, t)| t.apply(&p))
+    }
+}
+
+pub enum NormaliseUpdate {
+    Point(Array1<f32>),
+    Rescale(BoundingBox, Array1<f32>),
+}
+
+pub struct OnlineNormaliseRescale<I>(OnlineNormalise<I>);
+
+impl<S, I> OnlineNormaliseRescale<I>
+where
+    S: Data<Elem = f32>,
+    I: Iterator<Item = ArrayBase<S, Ix1>>,
+{
+    pub fn bounding_box(&self) -> Option<BoundingBox> {
+        self.0.bounding_box()
+    }
+
+    pub fn raw_bounding_box(&self) -> Option<BoundingBox> {
+        self.0.raw_bounding_box()
+    }
+}
+
+impl<S, I> Iterator for OnlineNormaliseRescale<I>
+where
+    S: Data<Elem = f32>,
+    I: Iterator<Item = ArrayBase<S, Ix1>>,
+{
+    type Item = NormaliseUpdate;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let p = self.0.next_raw(true)?;
+        let margin = self.0.margin;
+        let (stats, t) = self.0.frame.as_mut()?;
+        if t.raw_bb.contains(&p) {
+            Some(NormaliseUpdate::Point(t.apply(&p)))
+        } else {
+            *t = Transform::new(stats, margin);
+            Some(NormaliseUpdate::Rescale(t.bb.clone(), t.apply(&p)))
+        }
+    }
+}
+
+pub trait OnlineNormaliseIter<S: Data<Elem = f32>>:
+    Iterator<Item = ArrayBase<S, Ix1>> + Sized
+{
+    // Normalisation is fixed after the warm-up, so that all the points fed to a forest share its
+    // frame; points are clamped to `bounding_box()`.
+    fn online_normalise(self, cfg: &OnlineNormaliseConfig) -> OnlineNormalise<Self> {
+        OnlineNormalise::new(self, cfg)
+    }
+
+    // Normalisation is frozen until a point leaves the (padded) range seen so far, at which point
+    // it follows the running statistics and the new bounding box is emitted with the point.
+    fn online_normalise_rescaled(
+        self,
+        cfg: &OnlineNormaliseConfig,
+    ) -> OnlineNormaliseRescale<Self> {
+        OnlineNormaliseRescale(OnlineNormalise::new(self, cfg))
+    }
+}
+
+impl<S: Data<Elem = f32>, I: Iterator<Item = ArrayBase<S, Ix1>>> OnlineNormaliseIter<S> for I {}
@@ -21,11 +21,14 @@ impl ParStreamSampler {
         }
     }
 
-    pub fn insert(&mut self, m: usize, point: &Array1<f32>) {
+    // Whether machine `m` sends the point to the coordinator, which replies with a threshold.
+    pub fn insert(&mut self, m: usize, point: &Array1<f32>) -> bool {
         let w = self.rng.gen_range(0.0..1.0);
-        if w < self.us[m] {
+        let sent = w < self.us[m];
+        if sent {
             self.us[m] = self.update(point, w);
         }
+        sent
     }
 
     fn update(&mut self, point: &Array1<f32>, w: f64) -> f64 {
@@ -0,0 +1,163 @@
+use std::{collections::HashSet, hash::Hash};
+
+use ndarray::Array1;
+use rand::{prelude::StdRng, Rng, SeedableRng};
+use rand_distr::{Distribution, Uniform};
+
+use super::hash_picker::HashPicker;
+use crate::algorithm::config::Config;
+
+pub type Points = Vec<(usize, Array1<f32>)>;
+
+// Splits indexed points, in stream order, between machines. Machines may end up with no points.
+pub trait Partitioner {
+    fn partition(&self, points: Points, n_machines: usize) -> Vec<Points>;
+}
+
+fn assert_machines(n_machines: usize) {
+    assert!(n_machines > 0, "no machines to partition between");
+}
+
+fn by_machine<F>(points: Points, n_machines: usize, mut machine: F) -> Vec<Points>
+where
+    F: FnMut(usize, &Array1<f32>) -> usize,
+{
+    let mut machines = vec![Vec::new(); n_machines];
+    for (i, p) in points {
+        machines[machine(i, &p)].push((i, p));
+    }
+    machines
+}
+
+// Contiguous ranges of random sizes.
+pub struct RandomRanges {
+    seed: u64,
+}
+
+impl RandomRanges {
+    pub fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+
+    pub fn from_config(cfg: &Config) -> Self {
+        Self::new(cfg.get_rng().gen())
+    }
+}
+
+impl Partitioner for RandomRanges {
+    fn partition(&self, mut points: Points, n_machines: usize) -> Vec<Points> {
+        assert_machines(n_machines);
+        let n = points.len();
+        let mut splits: Vec<usize> = Uniform::new(0, n.max(1))
+            .sample_iter(StdRng::seed_from_u64(self.seed))
+            .take(n_machines - 1)
+            .chain([0, n])
+            .collect();
+        splits.sort_unstable();
+        let mut machines: Vec<_> = splits
+            .windows(2)
+            .rev()
+            .map(|win| points.split_off(points.len() - (win[1] - win[0])))
+            .collect();
+        machines.reverse();
+        machines
+    }
+}
+
+// Every point to a machine picked uniformly at random.
+pub struct RandomMachines {
+    seed: u64,
+}
+
+impl RandomMachines {
+    pub fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+
+    pub fn from_config(cfg: &Config) -> Self {
+        Self::new(cfg.get_rng().gen())
+    }
+}
+
+impl Partitioner for RandomMachines {
+    fn partition(&self, points: Points, n_machines: usize) -> Vec<Points> {
+        assert_machines(n_machines);
+        let mut picks = Uniform::new(0, n_machines).sample_iter(StdRng::seed_from_u64(self.seed));
+        by_machine(points, n_machines, |_, _| picks.next().unwrap())
+    }
+}
+
+pub struct RoundRobin;
+
+impl Partitioner for RoundRobin {
+    fn partition(&self, points: Points, n_machines: usize) -> Vec<Points> {
+        assert_machines(n_machines);
+        let mut machines = (0..n_machines).cycle();
+        by_machine(points, n_machines, |_, _| machines.next().unwrap())
+    }
+}
+
+// Contiguous ranges of equal sizes, as when every machine sees one period of time.
+pub struct TimeRange;
+
+impl Partitioner for TimeRange {
+    fn partition(&self, mut points: Points, n_machines: usize) -> Vec<Points> {
+        assert_machines(n_machines);
+        let size = points.len().div_ceil(n_machines);
+        let mut machines: Vec<_> = (0..n_machines)
+            .rev()
+            .map(|m| points.split_off((m * size).min(points.len())))
+            .collect();
+        machines.reverse();
+        machines
+    }
+}
+
+// All points of the same key to the same machine, by a seeded hash of the key.
+pub struct HashByKey<F> {
+    key: F,
+    seed: u64,
+}
+
+impl<K: Hash, F: Fn(usize, &Array1<f32>) -> K> HashByKey<F> {
+    pub fn new(key: F, seed: u64) -> Self {
+        Self { key, seed }
+    }
+}
+
+impl<K: Hash, F: Fn(usize, &Array1<f32>) -> K> Partitioner for HashByKey<F> {
+    fn partition(&self, points: Points, n_machines: usize) -> Vec<Points> {
+        assert_machines(n_machines);
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let picker = HashPicker::from_frac(1, n_machines, &mut rng);
+        by_machine(points, n_machines, |i, p| picker.bucket(&(self.key)(i, p)))
+    }
+}
+
+// All the given points, typically the labelled anomalies, to the first machine, and the others
+// round-robin.
+pub struct Skewed {
+    anomalies: HashSet<usize>,
+}
+
+impl Skewed {
+    pub fn new(anomalies: impl IntoIterator<Item = usize>) -> Self {
+        Self {
+            anomalies: anomalies.into_iter().collect(),
+        }
+    }
+}
+
+impl Partitioner for Skewed {
+    fn partition(&self, points: Points, n_machines: usize) -> Vec<Points> {
+        assert_machines(n_machines);
+        let mut machines = (0..n_machines).cycle();
+        by_machine(points, n_machines, |i, _| {
+            if self.anomalies.contains(&i) {
+                0
+            } else {
+                machines.next().unwrap()
+            }
+        })
+    }
+}
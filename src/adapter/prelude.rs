@@ -1,5 +1,19 @@
+#[cfg(feature = "stream")]
+pub use super::stream::{AdaptStream, RSFStream, SpotLightStream, TransformStream};
 pub use super::{
-    distributed::DistributedIter, normalise::NormaliseIter, rsf_reservoir::RSFReservoirIter,
-    rsf_split::RSFSplitIter, rsf_window::RSFWindowIter, shingle::ShingleIter, spotlight::Graph,
-    spotlight::SpotLightConfig, spotlight::SpotLightIter, transform::TransformIter,
+    aligned::AlignedIter, calibrate::CalibrateIter, calibrate::Calibration,
+    calibrate::ForestScores, distributed::CostReport, distributed::DistributedIter,
+    distributed::HierarchyConfig, distributed::RoundCost, graph_features::GraphFeatures,
+    graph_features::GraphFeaturesIter, keyed::KeyedConfig, keyed::KeyedIter,
+    monitor::MonitorConfig, monitor::MonitorIter, monitor::SiteDetector, normalise::NormaliseIter,
+    online_normalise::NormaliseUpdate, online_normalise::OnlineNormaliseConfig,
+    online_normalise::OnlineNormaliseIter, online_normalise::Scaling, partition::HashByKey,
+    partition::Partitioner, partition::RandomMachines, partition::RandomRanges,
+    partition::RoundRobin, partition::Skewed, partition::TimeRange, remote::RemoteIter,
+    rsf_reservoir::RSFReservoirIter, rsf_reservoir::ReservoirDetector, rsf_split::RSFSplitIter,
+    rsf_time_window::RSFTimeWindowIter, rsf_window::RSFWindowIter, rsf_window::WindowDetector,
+    shingle::ShingleConfig, shingle::ShingleIter, shingle::ShingleTransform,
+    spotlight::Attribution, spotlight::BucketConfig, spotlight::EdgeSpotLightIter,
+    spotlight::Graph, spotlight::SpotLightConfig, spotlight::SpotLightIter, threshold::SpotConfig,
+    threshold::ThresholdIter, transform::TransformIter,
 };
@@ -0,0 +1,232 @@
+use std::{
+    io::{self, Read, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+use ndarray::{Array1, ArrayBase, Data, Ix1};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    distributed::{partial_cost, partial_forest, top_candidates, CostReport, RoundCost},
+    partition::Points,
+};
+use crate::algorithm::{config::Config, forest::RSF};
+
+// Bumped whenever `Message` changes, so that mismatched workers and coordinators refuse each
+// other instead of misreading messages.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+// Most bytes a number takes in the JSON payload, with its separator: 20 digits for a `usize`,
+// fewer for an `f32`.
+const JSON_NUMBER_BYTES: u64 = 24;
+
+// A worker that stalls longer than this while sending is dropped by the coordinator.
+pub const READ_TIMEOUT: Duration = Duration::from_secs(60);
+
+fn invalid_data(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+// Points of every tree of a forest. Trees only depend on the config, so a forest built from the
+// same seeded config on another machine is the same once the points are inserted.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ForestSketch(pub Vec<Vec<(Vec<f32>, usize)>>);
+
+impl ForestSketch {
+    pub fn from_forest(f: &RSF) -> Self {
+        let trees = f
+            .points()
+            .into_iter()
+            .map(|points| points.into_iter().map(|(p, w)| (p.to_vec(), w)).collect())
+            .collect();
+        Self(trees)
+    }
+
+    pub fn into_forest(self, cfg: &Config) -> RSF {
+        let points: Vec<Vec<_>> = self
+            .0
+            .into_iter()
+            .map(|points| {
+                points
+                    .into_iter()
+                    .map(|(p, w)| (Array1::from(p), w))
+                    .collect()
+            })
+            .collect();
+        let mut f = RSF::from_config(cfg);
+        f.insert_points(&points);
+        f
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Message {
+    Partial {
+        machine: usize,
+        sketch: ForestSketch,
+        candidates: Vec<(usize, Vec<f32>)>,
+    },
+}
+
+// Largest payload of a partial forest under `cfg` with `n1` candidates. A machine samples
+// `n_points / n_machines` points per tree, and its sketch keeps at most `sketch_size` of them in
+// every leaf. Larger payloads are refused before anything is allocated for them.
+pub fn max_payload(cfg: &Config, n1: usize) -> u64 {
+    let n_leaves = 1usize
+        .checked_shl(cfg.max_depth() as u32)
+        .unwrap_or(usize::MAX);
+    let tree_points = (cfg.n_points / cfg.n_machines).min(n_leaves.saturating_mul(cfg.sketch_size));
+    let n_points = cfg.n_trees.saturating_mul(tree_points).saturating_add(n1) as u64;
+    // a point is its coordinates and its weight or index, and the rest is a few brackets and
+    // names per tree
+    let point_bytes = (cfg.bb.d() as u64 + 1) * JSON_NUMBER_BYTES;
+    n_points
+        .saturating_mul(point_bytes)
+        .saturating_add((cfg.n_trees as u64 + 1).saturating_mul(JSON_NUMBER_BYTES) * 4)
+}
+
+// Frames are the protocol version and the payload length, both big-endian, followed by the
+// payload in JSON. Returns the number of bytes written.
+pub fn send<W: Write>(w: &mut W, msg: &Message) -> io::Result<usize> {
+    let payload = serde_json::to_vec(msg)?;
+    w.write_all(&PROTOCOL_VERSION.to_be_bytes())?;
+    w.write_all(&(payload.len() as u64).to_be_bytes())?;
+    w.write_all(&payload)?;
+    w.flush()?;
+    Ok(4 + 8 + payload.len())
+}
+
+// Returns the message with the number of bytes read. Payloads over `max_payload` bytes are
+// refused.
+pub fn recv<R: Read>(r: &mut R, max_payload: u64) -> io::Result<(Message, usize)> {
+    let mut version = [0; 4];
+    r.read_exact(&mut version)?;
+    let version = u32::from_be_bytes(version);
+    if version != PROTOCOL_VERSION {
+        return Err(invalid_data(format!(
+            "protocol version {version}, expected {PROTOCOL_VERSION}"
+        )));
+    }
+    let mut len = [0; 8];
+    r.read_exact(&mut len)?;
+    let len = u64::from_be_bytes(len);
+    if len > max_payload {
+        return Err(invalid_data(format!(
+            "payload of {len} bytes, at most {max_payload}"
+        )));
+    }
+    let mut payload = vec![0; len as usize];
+    r.read_exact(&mut payload)?;
+    Ok((serde_json::from_slice(&payload)?, 4 + 8 + payload.len()))
+}
+
+// Partial forest and candidates sent by one connection, checked against the config, with the
+// cost of receiving them.
+fn recv_partial(
+    stream: &mut TcpStream,
+    cfg: &Config,
+    n1: usize,
+    received: &[bool],
+) -> io::Result<(RSF, Points, RoundCost)> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let (
+        Message::Partial {
+            machine,
+            sketch,
+            candidates,
+        },
+        n_bytes,
+    ) = recv(stream, max_payload(cfg, n1))?;
+    if machine >= cfg.n_machines {
+        return Err(invalid_data(format!("unknown machine {machine}")));
+    }
+    if received[machine] {
+        return Err(invalid_data(format!("machine {machine} sent twice")));
+    }
+    let d = cfg.bb.d();
+    let valid_points = sketch.0.iter().flatten().all(|(p, _)| p.len() == d)
+        && candidates.iter().all(|(_, p)| p.len() == d);
+    if sketch.0.len() != cfg.n_trees || !valid_points {
+        return Err(invalid_data(format!(
+            "sketch of machine {machine} does not fit the config"
+        )));
+    }
+    let f = sketch.into_forest(cfg);
+    let candidates: Points = candidates
+        .into_iter()
+        .map(|(i, p)| (i, Array1::from(p)))
+        .collect();
+    let cost = RoundCost {
+        bytes_sent: n_bytes,
+        ..partial_cost(cfg, 0, machine, &f, &candidates)
+    };
+    Ok((f, candidates, cost))
+}
+
+// Coordinator of `one_way_worker`: waits for the partial forests of the `cfg.n_machines`
+// machines, merges them and keeps the `n1` most anomalous of their candidates. The coordinator
+// and the workers must share the same seeded config. The cost report counts the bytes actually
+// received. A connection that fails, or sends a machine id out of range or already received, or
+// a sketch that does not fit the config, is dropped and the coordinator keeps waiting for the
+// machines left.
+pub fn one_way_coordinator(
+    listener: &TcpListener,
+    cfg: &Config,
+    n1: usize,
+) -> io::Result<(RSF, Vec<usize>, CostReport)> {
+    let mut sketch_sum = RSF::from_config(cfg);
+    let mut candidates = Vec::new();
+    let mut costs = Vec::new();
+    let mut received = vec![false; cfg.n_machines];
+    let mut n_left = cfg.n_machines;
+    while n_left > 0 {
+        let (mut stream, _) = listener.accept()?;
+        let Ok((f, machine_candidates, cost)) = recv_partial(&mut stream, cfg, n1, &received)
+        else {
+            continue;
+        };
+        received[cost.machine] = true;
+        n_left -= 1;
+        costs.push(cost);
+        sketch_sum.extend(f);
+        candidates.extend(machine_candidates);
+    }
+    let anomalies = top_candidates(&sketch_sum, candidates, n1);
+    Ok((sketch_sum, anomalies, CostReport(costs)))
+}
+
+pub trait RemoteIter<S: Data<Elem = f32>>:
+    Iterator<Item = (usize, ArrayBase<S, Ix1>)> + Sized
+{
+    // Builds the partial forest of machine `machine` over the points, identified by their index
+    // in the whole data, and sends it with its candidates to the coordinator at `addr`.
+    fn one_way_worker<A: ToSocketAddrs>(
+        self,
+        cfg: &Config,
+        n1: usize,
+        machine: usize,
+        addr: A,
+    ) -> io::Result<RoundCost> {
+        let mut points: Vec<_> = self.map(|(i, p)| (i, p.to_owned())).collect();
+        let f = partial_forest(cfg, machine, &mut points, n1);
+        let cost = partial_cost(cfg, 0, machine, &f, &points);
+        let msg = Message::Partial {
+            machine,
+            sketch: ForestSketch::from_forest(&f),
+            candidates: points.into_iter().map(|(i, p)| (i, p.to_vec())).collect(),
+        };
+        let n_bytes = send(&mut TcpStream::connect(addr)?, &msg)?;
+        Ok(RoundCost {
+            bytes_sent: n_bytes,
+            ..cost
+        })
+    }
+}
+
+impl<S, I> RemoteIter<S> for I
+where
+    S: Data<Elem = f32>,
+    I: Iterator<Item = (usize, ArrayBase<S, Ix1>)>,
+{
+}
@@ -1,6 +1,8 @@
-use std::mem;
+use std::{f64::consts::LN_2, mem};
 
-use rand::{prelude::ThreadRng, thread_rng, Rng};
+use rand::{rngs::StdRng, Rng};
+
+use crate::algorithm::config::Sampling;
 
 pub enum ReservoirUpdate<T> {
     Skip(T),
@@ -12,32 +14,100 @@ pub struct Reservoir<T> {
     r: usize,
     i: usize,
     buf: Vec<T>,
-    rng: ThreadRng,
+    keys: Vec<f64>,
+    sampling: Sampling,
+    rng: StdRng,
 }
 
 impl<T: Clone> Reservoir<T> {
-    pub fn new(r: usize) -> Self {
+    pub fn new(r: usize, sampling: Sampling, rng: StdRng) -> Self {
         Self {
             r,
             i: 0,
             buf: Vec::with_capacity(r),
-            rng: thread_rng(),
+            keys: Vec::with_capacity(r),
+            sampling,
+            rng,
         }
     }
 
+    pub fn is_full(&self) -> bool {
+        self.buf.len() == self.r
+    }
+
     pub fn insert(&mut self, item: T) -> ReservoirUpdate<T> {
         self.i += 1;
-        if self.buf.len() < self.r {
-            self.buf.push(item.clone());
-            ReservoirUpdate::Insert(item)
-        } else {
-            let j = self.rng.gen_range(0..self.i);
-            if j < self.r {
-                let old_item = mem::replace(&mut self.buf[j], item.clone());
-                ReservoirUpdate::Replace(old_item, item)
+        match self.sampling {
+            Sampling::Uniform => {
+                if self.buf.len() < self.r {
+                    return self.push(item, 0.);
+                }
+                let j = self.rng.gen_range(0..self.i);
+                if j < self.r {
+                    self.replace(j, item, 0.)
+                } else {
+                    ReservoirUpdate::Skip(item)
+                }
+            }
+            Sampling::Biased(half_life) => {
+                // a point enters with probability r * lambda and evicts a random point with
+                // probability equal to the fill fraction
+                let p_in = (self.r as f64 * LN_2 / half_life).min(1.);
+                let fill = self.buf.len() as f64 / self.r as f64;
+                if !self.rng.gen_bool(p_in) {
+                    ReservoirUpdate::Skip(item)
+                } else if self.buf.len() < self.r && !self.rng.gen_bool(fill) {
+                    self.push(item, 0.)
+                } else {
+                    let j = self.rng.gen_range(0..self.buf.len());
+                    self.replace(j, item, 0.)
+                }
+            }
+            Sampling::Priority(half_life) => {
+                // log of an exponential variate scaled down by the exponential weight of the point
+                let e = -(1. - self.rng.gen::<f64>()).ln();
+                let key = e.ln() - LN_2 * (self.i as f64) / half_life;
+                if self.buf.len() < self.r {
+                    return self.push(item, key);
+                }
+                let (j, &max) = self
+                    .keys
+                    .iter()
+                    .enumerate()
+                    .max_by(|(_, k1), (_, k2)| k1.total_cmp(k2))
+                    .unwrap();
+                if key < max {
+                    self.replace(j, item, key)
+                } else {
+                    ReservoirUpdate::Skip(item)
+                }
+            }
+        }
+    }
+
+    fn push(&mut self, item: T, key: f64) -> ReservoirUpdate<T> {
+        self.buf.push(item.clone());
+        self.keys.push(key);
+        ReservoirUpdate::Insert(item)
+    }
+
+    fn replace(&mut self, j: usize, item: T, key: f64) -> ReservoirUpdate<T> {
+        self.keys[j] = key;
+        let old_item = mem::replace(&mut self.buf[j], item.clone());
+        ReservoirUpdate::Replace(old_item, item)
+    }
+
+    pub fn remove<F: Fn(&T) -> bool>(&mut self, pred: F) -> Vec<T> {
+        let mut removed = Vec::new();
+        let mut j = 0;
+        while j < self.buf.len() {
+            if pred(&self.buf[j]) {
+                self.keys.swap_remove(j);
+                removed.push(self.buf.swap_remove(j));
             } else {
-                ReservoirUpdate::Skip(item)
+                j += 1;
             }
         }
+        removed
     }
 }
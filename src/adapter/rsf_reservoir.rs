@@ -1,45 +1,130 @@
-use ndarray::{ArrayBase, Data, Ix1, RawDataClone};
+use std::{collections::VecDeque, sync::Arc};
 
-use crate::algorithm::{config::Config, forest::RSF, tree::RandShiftTree};
+use ndarray::{Array1, ArrayBase, Data, Ix1};
+use rand::{rngs::StdRng, SeedableRng};
 
-use super::reservoir::{Reservoir, ReservoirUpdate};
+use crate::{
+    algorithm::{
+        config::{Config, UpdateMode},
+        forest::RSF,
+        tree::RandShiftTree,
+    },
+    score::PathLength,
+};
 
-pub struct RSFReservoir<I: Iterator, const M: bool> {
-    iter: I,
-    reservoirs: Vec<Reservoir<I::Item>>,
+use super::{
+    calibrate::ForestScores,
+    feedback::Feedback,
+    reservoir::{Reservoir, ReservoirUpdate},
+};
+
+pub struct ReservoirDetector<const M: bool> {
+    reservoirs: Vec<Reservoir<Arc<Array1<f32>>>>,
     f: RSF,
+    warm: bool,
+    mode: UpdateMode,
+    pending: VecDeque<Array1<f32>>,
+    i: usize,
+    feedback: Feedback,
 }
 
-impl<S, I, const M: bool> RSFReservoir<I, M>
-where
-    S: Data<Elem = f32> + RawDataClone,
-    I: Iterator<Item = ArrayBase<S, Ix1>>,
-{
-    fn new(iter: I, cfg: &Config) -> Self {
+impl<const M: bool> ReservoirDetector<M> {
+    pub fn new(cfg: &Config) -> Self {
         let n_reservoirs = if M { cfg.n_trees } else { 1 };
         let r = cfg.n_points;
-        let reservoirs = (0..n_reservoirs).map(|_| Reservoir::new(r)).collect();
+        // one independent stream per reservoir, reproducible when the config is seeded
+        let mut rng = cfg.get_rng();
+        let reservoirs = (0..n_reservoirs)
+            .map(|_| Reservoir::new(r, cfg.sampling, StdRng::from_rng(&mut rng).unwrap()))
+            .collect();
         let f = RSF::from_config(cfg);
-        let mut res = Self {
-            iter,
+        Self {
             reservoirs,
             f,
+            warm: false,
+            mode: cfg.update,
+            pending: VecDeque::new(),
+            i: 0,
+            feedback: Feedback::new(cfg.feedback_horizon as u64),
+        }
+    }
+
+    pub fn forest(&self) -> &RSF {
+        &self.f
+    }
+
+    pub fn score_only<S: Data<Elem = f32>>(&self, p: &ArrayBase<S, Ix1>) -> PathLength {
+        self.f.score(p)
+    }
+
+    // Scores `p` and offers it to the reservoir(s). Nothing is scored until they have all been
+    // full once, which takes longer than `n_points` points with biased or priority sampling.
+    pub fn update<S: Data<Elem = f32>>(&mut self, p: &ArrayBase<S, Ix1>) -> Option<PathLength> {
+        self.i += 1;
+        self.feedback.expire(&mut self.f, self.i as u64);
+        let ready = self.warm;
+        let s = match self.mode {
+            UpdateMode::ScoreThenUpdate => {
+                let s = self.f.score(p);
+                self.handle_new(Arc::new(p.to_owned()));
+                s
+            }
+            UpdateMode::UpdateThenScore => {
+                self.handle_new(Arc::new(p.to_owned()));
+                self.f.score(p)
+            }
+            UpdateMode::Delayed(d) => {
+                let s = self.f.score(p);
+                self.pending.push_back(p.to_owned());
+                if self.pending.len() > d {
+                    let q = self.pending.pop_front().unwrap();
+                    self.handle_new(Arc::new(q));
+                }
+                s
+            }
         };
-        res.by_ref().take(r).for_each(drop);
-        res
+        ready.then_some(s)
+    }
+
+    // Inserts `p` with `weight` in every tree, next to the reservoir(s) rather than in them, until
+    // `cfg.feedback_horizon` more points have been seen.
+    pub fn insert_normal<S: Data<Elem = f32>>(&mut self, p: &ArrayBase<S, Ix1>, weight: usize) {
+        self.feedback
+            .insert_normal(&mut self.f, p, weight, self.i as u64);
+    }
+
+    // The damping expires after `cfg.feedback_horizon` more points.
+    pub fn damp<S: Data<Elem = f32>>(&mut self, p: &ArrayBase<S, Ix1>, factor: f32) {
+        self.feedback.damp(&mut self.f, p, factor, self.i as u64);
+    }
+
+    // Takes the points equal to `p` out of the reservoir(s), they are no longer part of the model.
+    pub fn exclude<S: Data<Elem = f32>>(&mut self, p: &ArrayBase<S, Ix1>) {
+        self.pending.retain(|q| q != p);
+        if M {
+            for (tree, res) in self.f.iter_trees_mut().zip(self.reservoirs.iter_mut()) {
+                for q in res.remove(|q| **q == *p) {
+                    tree.remove(&*q);
+                }
+            }
+        } else {
+            for q in self.reservoirs[0].remove(|q| **q == *p) {
+                self.f.remove(&*q);
+            }
+        }
     }
 
-    fn handle_new(&mut self, item: ArrayBase<S, Ix1>) {
+    fn handle_new(&mut self, item: Arc<Array1<f32>>) {
         if M {
             for (tree, res) in self.f.iter_trees_mut().zip(self.reservoirs.iter_mut()) {
                 match res.insert(item.clone()) {
                     ReservoirUpdate::Skip(_p) => {}
                     ReservoirUpdate::Insert(p) => {
-                        tree.insert(&p);
+                        tree.insert(&*p);
                     }
                     ReservoirUpdate::Replace(q, p) => {
-                        tree.remove(&q);
-                        tree.insert(&p);
+                        tree.remove(&*q);
+                        tree.insert(&*p);
                     }
                 }
             }
@@ -47,34 +132,70 @@ where
             match self.reservoirs[0].insert(item) {
                 ReservoirUpdate::Skip(_p) => {}
                 ReservoirUpdate::Insert(p) => {
-                    self.f.insert(&p);
+                    self.f.insert(&*p);
                 }
                 ReservoirUpdate::Replace(q, p) => {
-                    self.f.remove(&q);
-                    self.f.insert(&p);
+                    self.f.remove(&*q);
+                    self.f.insert(&*p);
                 }
             }
         }
+        self.warm = self.warm || self.reservoirs.iter().all(Reservoir::is_full);
+    }
+}
+
+pub struct RSFReservoir<I, const M: bool> {
+    iter: I,
+    d: ReservoirDetector<M>,
+}
+
+impl<S, I, const M: bool> RSFReservoir<I, M>
+where
+    S: Data<Elem = f32>,
+    I: Iterator<Item = ArrayBase<S, Ix1>>,
+{
+    fn new(iter: I, cfg: &Config) -> Self {
+        let d = ReservoirDetector::new(cfg);
+        Self { iter, d }
+    }
+
+    pub fn forest(&self) -> &RSF {
+        self.d.forest()
+    }
+
+    pub fn detector_mut(&mut self) -> &mut ReservoirDetector<M> {
+        &mut self.d
     }
 }
 
-impl<'a, S, I, const M: bool> Iterator for RSFReservoir<I, M>
+impl<S, I, const M: bool> Iterator for RSFReservoir<I, M>
 where
-    S: Data<Elem = f32> + RawDataClone + 'a,
+    S: Data<Elem = f32>,
     I: Iterator<Item = ArrayBase<S, Ix1>>,
 {
-    type Item = f32;
+    type Item = PathLength;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.iter.next().map(|item| {
-            let s = self.f.score(&item);
-            self.handle_new(item);
-            s
-        })
+        loop {
+            let p = self.iter.next()?;
+            if let Some(s) = self.d.update(&p) {
+                return Some(s);
+            }
+        }
+    }
+}
+
+impl<S, I, const M: bool> ForestScores for RSFReservoir<I, M>
+where
+    S: Data<Elem = f32>,
+    I: Iterator<Item = ArrayBase<S, Ix1>>,
+{
+    fn forest(&self) -> &RSF {
+        self.forest()
     }
 }
 
-pub trait RSFReservoirIter<S: Data<Elem = f32> + RawDataClone>:
+pub trait RSFReservoirIter<S: Data<Elem = f32>>:
     Iterator<Item = ArrayBase<S, Ix1>> + Sized
 {
     fn rsf_reservoir<const M: bool>(self, cfg: &Config) -> RSFReservoir<Self, M> {
@@ -82,7 +203,4 @@ pub trait RSFReservoirIter<S: Data<Elem = f32> + RawDataClone>:
     }
 }
 
-impl<S: Data<Elem = f32> + RawDataClone, I: Iterator<Item = ArrayBase<S, Ix1>>> RSFReservoirIter<S>
-    for I
-{
-}
+impl<S: Data<Elem = f32>, I: Iterator<Item = ArrayBase<S, Ix1>>> RSFReservoirIter<S> for I {}
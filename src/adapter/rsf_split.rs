@@ -1,10 +1,16 @@
 use ndarray::{ArrayBase, Data, Ix1};
 
-use crate::algorithm::{config::Config, forest::RSF};
+use crate::{
+    algorithm::{config::Config, forest::RSF},
+    score::PathLength,
+};
+
+use super::calibrate::ForestScores;
 
 pub struct RSFSplit<I> {
     iter: I,
     f: RSF,
+    n_train: usize,
 }
 
 impl<S, I> RSFSplit<I>
@@ -12,10 +18,17 @@ where
     S: Data<Elem = f32>,
     I: Iterator<Item = ArrayBase<S, Ix1>>,
 {
-    fn new(mut iter: I, cfg: &Config) -> Self {
-        let mut f = RSF::from_config(cfg);
-        iter.by_ref().take(cfg.n_points).for_each(|p| f.insert(&p));
-        Self { iter, f }
+    fn new(iter: I, cfg: &Config) -> Self {
+        let f = RSF::from_config(cfg);
+        Self {
+            iter,
+            f,
+            n_train: cfg.n_points,
+        }
+    }
+
+    pub fn forest(&self) -> &RSF {
+        &self.f
     }
 }
 
@@ -24,10 +37,27 @@ where
     S: Data<Elem = f32>,
     I: Iterator<Item = ArrayBase<S, Ix1>>,
 {
-    type Item = f32;
+    type Item = PathLength;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.iter.next().map(|p| self.f.score(&p))
+        loop {
+            let p = self.iter.next()?;
+            if self.n_train == 0 {
+                return Some(self.f.score(&p));
+            }
+            self.f.insert(&p);
+            self.n_train -= 1;
+        }
+    }
+}
+
+impl<S, I> ForestScores for RSFSplit<I>
+where
+    S: Data<Elem = f32>,
+    I: Iterator<Item = ArrayBase<S, Ix1>>,
+{
+    fn forest(&self) -> &RSF {
+        self.forest()
     }
 }
 
@@ -0,0 +1,274 @@
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+
+use ndarray::{Array1, ArrayBase, Data, Ix1};
+use rand::{rngs::StdRng, Rng};
+
+use crate::{
+    algorithm::{
+        config::{Config, UpdateMode},
+        forest::RSF,
+        tree::RandShiftTree,
+    },
+    score::PathLength,
+};
+
+use super::feedback::Feedback;
+
+// Keeps the `n` items of lowest priority, so the sample stays uniform over the window and its
+// size stays bounded however many items the window holds.
+struct PrioritySample {
+    n: usize,
+    priorities: HashMap<usize, u64>,
+    sampled: BTreeSet<(u64, usize)>,
+    reserve: BTreeSet<(u64, usize)>,
+}
+
+enum SampleUpdate {
+    Skip,
+    Insert,
+    Replace(usize),
+}
+
+impl PrioritySample {
+    fn new(n: usize) -> Self {
+        Self {
+            n,
+            priorities: HashMap::new(),
+            sampled: BTreeSet::new(),
+            reserve: BTreeSet::new(),
+        }
+    }
+
+    fn insert(&mut self, i: usize, priority: u64) -> SampleUpdate {
+        self.priorities.insert(i, priority);
+        let key = (priority, i);
+        if self.sampled.len() < self.n {
+            self.sampled.insert(key);
+            return SampleUpdate::Insert;
+        }
+        match self.sampled.last().copied() {
+            Some(max) if key < max => {
+                self.sampled.remove(&max);
+                self.reserve.insert(max);
+                self.sampled.insert(key);
+                SampleUpdate::Replace(max.1)
+            }
+            _ => {
+                self.reserve.insert(key);
+                SampleUpdate::Skip
+            }
+        }
+    }
+
+    // Returns whether `i` was sampled and which item, if any, took its place.
+    fn evict(&mut self, i: usize) -> (bool, Option<usize>) {
+        let priority = self.priorities.remove(&i).unwrap();
+        let key = (priority, i);
+        if self.sampled.remove(&key) {
+            let promoted = self.reserve.pop_first();
+            if let Some(key) = promoted {
+                self.sampled.insert(key);
+            }
+            (true, promoted.map(|(_p, j)| j))
+        } else {
+            self.reserve.remove(&key);
+            (false, None)
+        }
+    }
+}
+
+pub struct RSFTimeWindow<I, const M: bool> {
+    iter: I,
+    span: u64,
+    max_points: usize,
+    f: RSF,
+    samples: Vec<PrioritySample>,
+    buf: VecDeque<(u64, Array1<f32>)>,
+    i: usize,
+    rng: StdRng,
+    mode: UpdateMode,
+    pending: VecDeque<(u64, Array1<f32>)>,
+    excluded: HashSet<usize>,
+    now: u64,
+    feedback: Feedback,
+}
+
+impl<S, I, const M: bool> RSFTimeWindow<I, M>
+where
+    S: Data<Elem = f32>,
+    I: Iterator<Item = (u64, ArrayBase<S, Ix1>)>,
+{
+    fn new(iter: I, cfg: &Config, span: u64, max_points: usize) -> Self {
+        assert!(span > 0, "invalid time span");
+        assert!(max_points >= cfg.n_points, "window smaller than the sample");
+        let n_samples = if M { cfg.n_trees } else { 1 };
+        let samples = (0..n_samples)
+            .map(|_| PrioritySample::new(cfg.n_points))
+            .collect();
+        Self {
+            iter,
+            span,
+            max_points,
+            f: RSF::from_config(cfg),
+            samples,
+            buf: VecDeque::new(),
+            i: 0,
+            rng: cfg.get_rng(),
+            mode: cfg.update,
+            pending: VecDeque::new(),
+            excluded: HashSet::new(),
+            now: 0,
+            feedback: Feedback::new(span),
+        }
+    }
+
+    pub fn forest(&self) -> &RSF {
+        &self.f
+    }
+
+    // Inserts `p` with `weight` in every tree, next to the window rather than in it, until the
+    // window has moved on by `span`.
+    pub fn insert_normal<S2: Data<Elem = f32>>(&mut self, p: &ArrayBase<S2, Ix1>, weight: usize) {
+        self.feedback
+            .insert_normal(&mut self.f, p, weight, self.now);
+    }
+
+    // The damping expires when the window has moved on by `span`.
+    pub fn damp<S2: Data<Elem = f32>>(&mut self, p: &ArrayBase<S2, Ix1>, factor: f32) {
+        self.feedback.damp(&mut self.f, p, factor, self.now);
+    }
+
+    // Takes the points equal to `p` out of the window, they are no longer part of the model.
+    pub fn exclude<S2: Data<Elem = f32>>(&mut self, p: &ArrayBase<S2, Ix1>) {
+        self.pending.retain(|(_, q)| q != p);
+        let first = self.i - self.buf.len();
+        let items: Vec<_> = (first..self.i)
+            .filter(|j| self.point(*j) == p && !self.excluded.contains(j))
+            .collect();
+        for j in items {
+            self.handle_evict(j);
+            self.excluded.insert(j);
+        }
+    }
+
+    fn point(&self, j: usize) -> &Array1<f32> {
+        let first = self.i - self.buf.len();
+        &self.buf[j - first].1
+    }
+
+    // Swaps `old` for `new` in the tree(s) fed by the `k`-th sample.
+    fn swap(&mut self, k: usize, old: Option<Array1<f32>>, new: Option<Array1<f32>>) {
+        if M {
+            let tree = &mut self.f[k];
+            old.iter().for_each(|p| tree.remove(p));
+            new.iter().for_each(|p| tree.insert(p));
+        } else {
+            old.iter().for_each(|p| self.f.remove(p));
+            new.iter().for_each(|p| self.f.insert(p));
+        }
+    }
+
+    // Takes the `j`-th point out of the samples.
+    fn handle_evict(&mut self, j: usize) {
+        for k in 0..self.samples.len() {
+            if let (true, promoted) = self.samples[k].evict(j) {
+                let old = self.point(j).clone();
+                let new = promoted.map(|j| self.point(j).clone());
+                self.swap(k, Some(old), new);
+            }
+        }
+    }
+
+    fn handle_old(&mut self) {
+        let i = self.i - self.buf.len();
+        if !self.excluded.remove(&i) {
+            self.handle_evict(i);
+        }
+        self.buf.pop_front();
+    }
+
+    fn handle_new(&mut self, t: u64, p: Array1<f32>) {
+        let i = self.i;
+        self.i += 1;
+        self.buf.push_back((t, p));
+        for k in 0..self.samples.len() {
+            let priority = self.rng.gen();
+            match self.samples[k].insert(i, priority) {
+                SampleUpdate::Skip => {}
+                SampleUpdate::Insert => self.swap(k, None, Some(self.point(i).clone())),
+                SampleUpdate::Replace(j) => {
+                    let (old, new) = (self.point(j).clone(), self.point(i).clone());
+                    self.swap(k, Some(old), Some(new));
+                }
+            }
+        }
+    }
+}
+
+impl<S, I, const M: bool> Iterator for RSFTimeWindow<I, M>
+where
+    S: Data<Elem = f32>,
+    I: Iterator<Item = (u64, ArrayBase<S, Ix1>)>,
+{
+    type Item = PathLength;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (t, p) = self.iter.next()?;
+            self.now = t;
+            self.feedback.expire(&mut self.f, t);
+            while let Some(&(t_old, _)) = self.buf.front() {
+                if t.saturating_sub(t_old) < self.span && self.buf.len() < self.max_points {
+                    break;
+                }
+                self.handle_old();
+            }
+            let s = match self.mode {
+                UpdateMode::ScoreThenUpdate => {
+                    let s = self.f.score(&p);
+                    self.handle_new(t, p.to_owned());
+                    s
+                }
+                UpdateMode::UpdateThenScore => {
+                    self.handle_new(t, p.to_owned());
+                    self.f.score(&p)
+                }
+                UpdateMode::Delayed(d) => {
+                    let s = self.f.score(&p);
+                    self.pending.push_back((t, p.to_owned()));
+                    if self.pending.len() > d {
+                        let (t, q) = self.pending.pop_front().unwrap();
+                        self.handle_new(t, q);
+                    }
+                    s
+                }
+            };
+            // Scores are only emitted once a full span has been observed.
+            if self.i > self.buf.len() {
+                return Some(s);
+            }
+        }
+    }
+}
+
+pub trait RSFTimeWindowIter<S: Data<Elem = f32>>:
+    Iterator<Item = (u64, ArrayBase<S, Ix1>)> + Sized
+{
+    // The window holds the points of the last `span` time units, but no more than `cfg.window`:
+    // each of them costs a priority in every sample, so bursts push the oldest points out early.
+    fn rsf_time_window<const M: bool>(self, cfg: &Config, span: u64) -> RSFTimeWindow<Self, M> {
+        RSFTimeWindow::new(self, cfg, span, cfg.window)
+    }
+
+    // Like `rsf_time_window`, with `max_points` in place of `cfg.window`.
+    fn rsf_time_window_capped<const M: bool>(
+        self,
+        cfg: &Config,
+        span: u64,
+        max_points: usize,
+    ) -> RSFTimeWindow<Self, M> {
+        RSFTimeWindow::new(self, cfg, span, max_points)
+    }
+}
+
+impl<S: Data<Elem = f32>, I: Iterator<Item = (u64, ArrayBase<S, Ix1>)>> RSFTimeWindowIter<S> for I {}
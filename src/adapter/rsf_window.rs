@@ -1,36 +1,127 @@
-use std::iter::Enumerate;
+use std::collections::{HashSet, VecDeque};
 
-use ndarray::{ArrayBase, Data, Ix1, RawDataClone};
+use ndarray::{Array1, ArrayBase, Data, Ix1};
 
 use crate::{
-    adapter::window::{Window, WindowIterator, WindowUpdate},
-    algorithm::{config::Config, forest::RSF, tree::RandShiftTree},
+    algorithm::{
+        config::{Config, UpdateMode},
+        forest::RSF,
+        tree::RandShiftTree,
+    },
+    score::PathLength,
 };
 
-use super::hash_picker::HashPicker;
+use super::{calibrate::ForestScores, feedback::Feedback, hash_picker::HashPicker};
 
-pub struct RSFWindow<I: Iterator, const M: bool> {
-    iter: Window<Enumerate<I>>,
+pub struct WindowDetector<const M: bool> {
     f: RSF,
     pickers: Vec<HashPicker>,
+    buf: VecDeque<(usize, Array1<f32>)>,
+    window: usize,
+    i: usize,
+    mode: UpdateMode,
+    pending: VecDeque<Array1<f32>>,
+    excluded: HashSet<usize>,
+    feedback: Feedback,
 }
 
-impl<'a, S, I, const M: bool> RSFWindow<I, M>
-where
-    S: Data<Elem = f32> + 'a,
-    I: Iterator<Item = ArrayBase<S, Ix1>>,
-{
-    fn new(items: I, cfg: &Config) -> Self {
+impl<const M: bool> WindowDetector<M> {
+    pub fn new(cfg: &Config) -> Self {
         let n_pickers = if M { cfg.n_trees } else { 1 };
+        let mut rng = cfg.get_rng();
         let pickers = (0..n_pickers)
-            .map(|_| HashPicker::from_frac(cfg.n_points, cfg.window))
+            .map(|_| HashPicker::from_frac(cfg.n_points, cfg.window, &mut rng))
+            .collect();
+        Self {
+            f: RSF::from_config(cfg),
+            pickers,
+            buf: VecDeque::with_capacity(cfg.window),
+            window: cfg.window,
+            i: 0,
+            mode: cfg.update,
+            pending: VecDeque::new(),
+            excluded: HashSet::new(),
+            feedback: Feedback::new(cfg.feedback_horizon as u64),
+        }
+    }
+
+    pub fn forest(&self) -> &RSF {
+        &self.f
+    }
+
+    pub fn score_only<S: Data<Elem = f32>>(&self, p: &ArrayBase<S, Ix1>) -> PathLength {
+        self.f.score(p)
+    }
+
+    // Scores `p` and slides the window over it. Nothing is scored until the window is full.
+    pub fn update<S: Data<Elem = f32>>(&mut self, p: &ArrayBase<S, Ix1>) -> Option<PathLength> {
+        let ready = self.buf.len() == self.window;
+        let s = match self.mode {
+            UpdateMode::ScoreThenUpdate => {
+                let s = self.f.score(p);
+                self.slide(p.to_owned());
+                s
+            }
+            UpdateMode::UpdateThenScore => {
+                self.slide(p.to_owned());
+                self.f.score(p)
+            }
+            UpdateMode::Delayed(d) => {
+                let s = self.f.score(p);
+                self.pending.push_back(p.to_owned());
+                if self.pending.len() > d {
+                    let q = self.pending.pop_front().unwrap();
+                    self.slide(q);
+                }
+                s
+            }
+        };
+        ready.then_some(s)
+    }
+
+    // Inserts `p` with `weight` in every tree, next to the window rather than in it, until
+    // `cfg.feedback_horizon` more points have been seen.
+    pub fn insert_normal<S: Data<Elem = f32>>(&mut self, p: &ArrayBase<S, Ix1>, weight: usize) {
+        self.feedback
+            .insert_normal(&mut self.f, p, weight, self.i as u64);
+    }
+
+    // The damping expires after `cfg.feedback_horizon` more points, by default when the window
+    // slides past the current point.
+    pub fn damp<S: Data<Elem = f32>>(&mut self, p: &ArrayBase<S, Ix1>, factor: f32) {
+        self.feedback.damp(&mut self.f, p, factor, self.i as u64);
+    }
+
+    // Takes the points equal to `p` out of the window, they are no longer part of the model.
+    pub fn exclude<S: Data<Elem = f32>>(&mut self, p: &ArrayBase<S, Ix1>) {
+        self.pending.retain(|q| q != p);
+        let items: Vec<_> = self
+            .buf
+            .iter()
+            .filter(|(i, q)| q == p && !self.excluded.contains(i))
+            .cloned()
             .collect();
-        let iter = items.enumerate().window(cfg.window);
-        let f = RSF::from_config(cfg);
-        Self { iter, f, pickers }
+        for item in items {
+            self.handle_old(&item);
+            self.excluded.insert(item.0);
+        }
     }
 
-    fn handle_old(&mut self, item: (usize, I::Item)) {
+    fn slide(&mut self, p: Array1<f32>) {
+        let item = (self.i, p);
+        self.i += 1;
+        self.feedback.expire(&mut self.f, self.i as u64);
+        if self.buf.len() == self.window {
+            let old_item = self.buf.pop_front().unwrap();
+            if !self.excluded.remove(&old_item.0) {
+                self.handle_old(&old_item);
+            }
+        }
+        self.handle_new(&item);
+        self.buf.push_back(item);
+    }
+
+    fn handle_old(&mut self, item: &(usize, Array1<f32>)) {
         if M {
             for (tree, picker) in self.f.iter_trees_mut().zip(self.pickers.iter()) {
                 if picker.picks(&item.0) {
@@ -42,7 +133,7 @@ where
         }
     }
 
-    fn handle_new(&mut self, item: (usize, I::Item)) {
+    fn handle_new(&mut self, item: &(usize, Array1<f32>)) {
         if M {
             for (tree, picker) in self.f.iter_trees_mut().zip(self.pickers.iter()) {
                 if picker.picks(&item.0) {
@@ -55,31 +146,57 @@ where
     }
 }
 
-impl<'a, S, I, const M: bool> Iterator for RSFWindow<I, M>
+pub struct RSFWindow<I, const M: bool> {
+    iter: I,
+    d: WindowDetector<M>,
+}
+
+impl<S, I, const M: bool> RSFWindow<I, M>
+where
+    S: Data<Elem = f32>,
+    I: Iterator<Item = ArrayBase<S, Ix1>>,
+{
+    fn new(iter: I, cfg: &Config) -> Self {
+        let d = WindowDetector::new(cfg);
+        Self { iter, d }
+    }
+
+    pub fn forest(&self) -> &RSF {
+        self.d.forest()
+    }
+
+    pub fn detector_mut(&mut self) -> &mut WindowDetector<M> {
+        &mut self.d
+    }
+}
+
+impl<S, I, const M: bool> Iterator for RSFWindow<I, M>
 where
-    S: Data<Elem = f32> + RawDataClone + 'a,
+    S: Data<Elem = f32>,
     I: Iterator<Item = ArrayBase<S, Ix1>>,
 {
-    type Item = f32;
+    type Item = PathLength;
 
     fn next(&mut self) -> Option<Self::Item> {
         loop {
-            match self.iter.next() {
-                None => return None,
-                Some(window_update) => match window_update {
-                    WindowUpdate::Insert(new_item) => self.handle_new(new_item),
-                    WindowUpdate::Replace(old_item, new_item) => {
-                        let s = self.f.score(&new_item.1);
-                        self.handle_old(old_item);
-                        self.handle_new(new_item);
-                        return Some(s);
-                    }
-                },
+            let p = self.iter.next()?;
+            if let Some(s) = self.d.update(&p) {
+                return Some(s);
             }
         }
     }
 }
 
+impl<S, I, const M: bool> ForestScores for RSFWindow<I, M>
+where
+    S: Data<Elem = f32>,
+    I: Iterator<Item = ArrayBase<S, Ix1>>,
+{
+    fn forest(&self) -> &RSF {
+        self.forest()
+    }
+}
+
 pub trait RSFWindowIter<S: Data<Elem = f32>>: Iterator<Item = ArrayBase<S, Ix1>> + Sized {
     fn rsf_window<const M: bool>(self, cfg: &Config) -> RSFWindow<Self, M> {
         RSFWindow::new(self, cfg)
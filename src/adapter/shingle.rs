@@ -1,6 +1,8 @@
 use std::collections::VecDeque;
 
-use ndarray::{Array1, ArrayBase, Data, Ix1};
+use ndarray::{Array1, ArrayBase, ArrayView1, Data, Ix1, Zip};
+
+pub use crate::algorithm::config::{ShingleConfig, ShingleTransform};
 
 pub struct Shingle<I: Iterator> {
     iter: I,
@@ -40,10 +42,81 @@ impl<S: Data<Elem = f32>, I: Iterator<Item = ArrayBase<S, Ix1>>> Iterator for Sh
     }
 }
 
+fn lagged(
+    transform: ShingleTransform,
+    current: ArrayView1<f32>,
+    past: ArrayView1<f32>,
+) -> Array1<f32> {
+    match transform {
+        ShingleTransform::Raw => past.to_owned(),
+        ShingleTransform::Diff => &current - &past,
+        ShingleTransform::Ratio => {
+            Zip::from(&current)
+                .and(&past)
+                .map_collect(|&c, &p| if p == 0. { 0. } else { c / p })
+        }
+    }
+}
+
+pub struct MultiShingle<I: Iterator> {
+    iter: I,
+    buf: VecDeque<I::Item>,
+    cfg: ShingleConfig,
+    skip: usize,
+}
+
+impl<S: Data<Elem = f32>, I: Iterator<Item = ArrayBase<S, Ix1>>> MultiShingle<I> {
+    fn new(iter: I, cfg: &ShingleConfig) -> Self {
+        let buf = VecDeque::with_capacity(cfg.max_lag() + 1);
+        let cfg = cfg.clone();
+        Self {
+            iter,
+            buf,
+            cfg,
+            skip: 0,
+        }
+    }
+
+    fn shingled_item(&self) -> Array1<f32> {
+        let current = self.buf.back().unwrap().view();
+        let mut parts = vec![current.to_owned()];
+        parts.extend(self.cfg.lags.iter().map(|&l| {
+            let past = self.buf[self.buf.len() - 1 - l].view();
+            lagged(self.cfg.transform, current, past)
+        }));
+        parts.into_iter().flatten().collect()
+    }
+}
+
+impl<S: Data<Elem = f32>, I: Iterator<Item = ArrayBase<S, Ix1>>> Iterator for MultiShingle<I> {
+    type Item = Array1<f32>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let item = self.iter.next()?;
+            if self.buf.len() > self.cfg.max_lag() {
+                self.buf.pop_front();
+            }
+            self.buf.push_back(item);
+            if self.buf.len() > self.cfg.max_lag() {
+                if self.skip == 0 {
+                    self.skip = self.cfg.stride - 1;
+                    return Some(self.shingled_item());
+                }
+                self.skip -= 1;
+            }
+        }
+    }
+}
+
 pub trait ShingleIter<S: Data<Elem = f32>>: Iterator<Item = ArrayBase<S, Ix1>> + Sized {
     fn shingle(self, s: usize) -> Shingle<Self> {
         Shingle::new(self, s)
     }
+
+    fn multi_shingle(self, cfg: &ShingleConfig) -> MultiShingle<Self> {
+        MultiShingle::new(self, cfg)
+    }
 }
 
 impl<S: Data<Elem = f32>, I: Iterator<Item = ArrayBase<S, Ix1>>> ShingleIter<S> for I {}
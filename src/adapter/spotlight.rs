@@ -1,6 +1,10 @@
-use std::hash::Hash;
+use std::{
+    collections::{HashMap, VecDeque},
+    hash::Hash,
+};
 
 use ndarray::Array1;
+use rand::{prelude::StdRng, thread_rng, SeedableRng};
 
 use super::hash_picker::HashPicker;
 
@@ -19,15 +23,57 @@ impl<S, D> Graph<S, D> {
     }
 }
 
+#[derive(Clone)]
 pub struct SpotLightConfig {
     pub k: usize,
     pub p: f64,
     pub q: f64,
+    pub heavy_hitters: Option<usize>,
+    pub seed: Option<u64>,
 }
 
 impl SpotLightConfig {
     pub fn new(k: usize, p: f64, q: f64) -> Self {
-        Self { k, p, q }
+        Self {
+            k,
+            p,
+            q,
+            heavy_hitters: None,
+            seed: None,
+        }
+    }
+
+    // Sketches of the same seed are comparable, whichever process computed them.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    fn get_rng(&self) -> StdRng {
+        if let Some(seed) = self.seed {
+            SeedableRng::seed_from_u64(seed)
+        } else {
+            SeedableRng::from_rng(thread_rng()).unwrap()
+        }
+    }
+
+    pub(super) fn pickers(&self) -> (Vec<HashPicker>, Vec<HashPicker>) {
+        let mut rng = self.get_rng();
+        let src_pickers = (0..self.k)
+            .map(|_| HashPicker::from_prob(self.p, &mut rng))
+            .collect();
+        let dst_pickers = (0..self.k)
+            .map(|_| HashPicker::from_prob(self.q, &mut rng))
+            .collect();
+        (src_pickers, dst_pickers)
+    }
+
+    // Number of heavy hitter sources and destinations tracked per sketch dimension by the edge
+    // stream SpotLight, for attribution.
+    pub fn heavy_hitters(mut self, capacity: usize) -> Self {
+        assert!(capacity > 0, "invalid heavy hitter capacity");
+        self.heavy_hitters = Some(capacity);
+        self
     }
 }
 
@@ -39,9 +85,8 @@ pub struct SpotLight<I> {
 }
 
 impl<I> SpotLight<I> {
-    fn new(iter: I, cfg: &SpotLightConfig) -> Self {
-        let src_pickers = (0..cfg.k).map(|_| HashPicker::from_prob(cfg.p)).collect();
-        let dst_pickers = (0..cfg.k).map(|_| HashPicker::from_prob(cfg.q)).collect();
+    pub(super) fn new(iter: I, cfg: &SpotLightConfig) -> Self {
+        let (src_pickers, dst_pickers) = cfg.pickers();
         Self {
             iter,
             k: cfg.k,
@@ -50,7 +95,7 @@ impl<I> SpotLight<I> {
         }
     }
 
-    fn sketch<S: Hash, D: Hash>(&self, g: Graph<S, D>) -> Array1<f32> {
+    pub(super) fn sketch<S: Hash, D: Hash>(&self, g: Graph<S, D>) -> Array1<f32> {
         let mut v = Array1::zeros(self.k);
         for (src, dst, w) in g.edges {
             for i in 0..self.k {
@@ -83,3 +128,363 @@ pub trait SpotLightIter<S: Hash, D: Hash>: Iterator<Item = Graph<S, D>> + Sized
 }
 
 impl<S: Hash, D: Hash, I: Iterator<Item = Graph<S, D>>> SpotLightIter<S, D> for I {}
+
+#[derive(Clone, Copy, Debug)]
+pub struct BucketConfig {
+    pub width: u64,
+    pub step: u64,
+}
+
+impl BucketConfig {
+    pub fn tumbling(width: u64) -> Self {
+        Self::sliding(width, width)
+    }
+
+    pub fn sliding(width: u64, step: u64) -> Self {
+        assert!(
+            step > 0 && width / step * step == width,
+            "invalid bucket step"
+        );
+        Self { width, step }
+    }
+}
+
+// Space-Saving summary of the heaviest keys.
+#[derive(Clone)]
+struct HeavyHitters<T> {
+    capacity: usize,
+    weights: HashMap<T, f32>,
+}
+
+impl<T: Hash + Eq + Clone> HeavyHitters<T> {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            weights: HashMap::with_capacity(capacity),
+        }
+    }
+
+    fn insert(&mut self, key: &T, w: f32) {
+        if let Some(weight) = self.weights.get_mut(key) {
+            *weight += w;
+        } else if self.weights.len() < self.capacity {
+            self.weights.insert(key.clone(), w);
+        } else {
+            let (min_key, min) = self
+                .weights
+                .iter()
+                .min_by(|(_, w1), (_, w2)| w1.total_cmp(w2))
+                .map(|(k, &w)| (k.clone(), w))
+                .unwrap();
+            self.weights.remove(&min_key);
+            self.weights.insert(key.clone(), min + w);
+        }
+    }
+
+    fn merge(&mut self, other: &Self) {
+        other.weights.iter().for_each(|(k, &w)| self.insert(k, w));
+    }
+
+    fn top(&self, n: usize) -> Vec<(T, f32)> {
+        let mut top: Vec<_> = self.weights.iter().map(|(k, &w)| (k.clone(), w)).collect();
+        top.sort_unstable_by(|(_, w1), (_, w2)| w2.total_cmp(w1));
+        top.truncate(n);
+        top
+    }
+}
+
+// Sources and destinations behind every dimension of a bucket sketch, and how far each
+// dimension deviates from the previous buckets (in standard deviations).
+pub struct Attribution<S, D> {
+    pub deviation: Array1<f32>,
+    capacity: usize,
+    src: Vec<HeavyHitters<S>>,
+    dst: Vec<HeavyHitters<D>>,
+}
+
+impl<S: Hash + Eq + Clone, D: Hash + Eq + Clone> Attribution<S, D> {
+    pub fn top_dims(&self, n: usize) -> Vec<usize> {
+        let mut dims: Vec<_> = (0..self.deviation.len()).collect();
+        dims.sort_unstable_by(|&i, &j| self.deviation[j].abs().total_cmp(&self.deviation[i].abs()));
+        dims.truncate(n);
+        dims
+    }
+
+    pub fn top_src(&self, dims: &[usize], n: usize) -> Vec<(S, f32)> {
+        let mut hh = HeavyHitters::new(self.capacity);
+        dims.iter().for_each(|&i| hh.merge(&self.src[i]));
+        hh.top(n)
+    }
+
+    pub fn top_dst(&self, dims: &[usize], n: usize) -> Vec<(D, f32)> {
+        let mut hh = HeavyHitters::new(self.capacity);
+        dims.iter().for_each(|&i| hh.merge(&self.dst[i]));
+        hh.top(n)
+    }
+}
+
+type Bucket<S, D> = (u64, Array1<f32>, Option<Attribution<S, D>>);
+
+struct Step<S, D> {
+    sketch: Array1<f32>,
+    src: Vec<HeavyHitters<S>>,
+    dst: Vec<HeavyHitters<D>>,
+}
+
+// SpotLight over a stream of timestamped edges. Every step of a bucket is kept separately, so
+// a sliding bucket is just the sum of its last `width / step` steps. Which subgraphs a node
+// belongs to is cached for the current step.
+pub struct EdgeSpotLight<I, S, D> {
+    iter: I,
+    k: usize,
+    src_pickers: Vec<HashPicker>,
+    dst_pickers: Vec<HashPicker>,
+    buckets: BucketConfig,
+    heavy_hitters: Option<usize>,
+    steps: VecDeque<Step<S, D>>,
+    step: Option<u64>,
+    src_cache: HashMap<S, Vec<usize>>,
+    dst_cache: HashMap<D, Vec<usize>>,
+    n_closed: usize,
+    mean: Array1<f32>,
+    m2: Array1<f32>,
+    out: VecDeque<Bucket<S, D>>,
+    done: bool,
+}
+
+impl<I, S, D> EdgeSpotLight<I, S, D>
+where
+    S: Hash + Eq + Clone,
+    D: Hash + Eq + Clone,
+{
+    fn new(iter: I, cfg: &SpotLightConfig, buckets: &BucketConfig) -> Self {
+        let (src_pickers, dst_pickers) = cfg.pickers();
+        Self {
+            iter,
+            k: cfg.k,
+            src_pickers,
+            dst_pickers,
+            buckets: *buckets,
+            heavy_hitters: cfg.heavy_hitters,
+            steps: VecDeque::new(),
+            step: None,
+            src_cache: HashMap::new(),
+            dst_cache: HashMap::new(),
+            n_closed: 0,
+            mean: Array1::zeros(cfg.k),
+            m2: Array1::zeros(cfg.k),
+            out: VecDeque::new(),
+            done: false,
+        }
+    }
+
+    fn new_step(&self) -> Step<S, D> {
+        let n = if self.heavy_hitters.is_some() {
+            self.k
+        } else {
+            0
+        };
+        let capacity = self.heavy_hitters.unwrap_or(0);
+        Step {
+            sketch: Array1::zeros(self.k),
+            src: vec![HeavyHitters::new(capacity); n],
+            dst: vec![HeavyHitters::new(capacity); n],
+        }
+    }
+
+    fn attribution(&self, sketch: &Array1<f32>) -> Attribution<S, D> {
+        let capacity = self.heavy_hitters.unwrap();
+        let mut src = vec![HeavyHitters::new(capacity); self.k];
+        let mut dst = vec![HeavyHitters::new(capacity); self.k];
+        for step in &self.steps {
+            for i in 0..self.k {
+                src[i].merge(&step.src[i]);
+                dst[i].merge(&step.dst[i]);
+            }
+        }
+        let var = &self.m2 / (self.n_closed.max(1) as f32);
+        let mut deviation = sketch - &self.mean;
+        deviation.zip_mut_with(&var, |d, &v| {
+            if v > 0. {
+                *d /= v.sqrt()
+            }
+        });
+        Attribution {
+            deviation,
+            capacity,
+            src,
+            dst,
+        }
+    }
+
+    fn n_steps(&self) -> u64 {
+        self.buckets.width / self.buckets.step
+    }
+
+    // Emits the bucket ending with the current step, once `width` has passed since the first
+    // edge so that it is full.
+    fn close_step(&mut self) {
+        let step = self.step.unwrap();
+        if self.steps.len() as u64 == self.n_steps() {
+            // no `(step + 1) * step` here, it overflows for edges near `u64::MAX`
+            let start = (step + 1 - self.n_steps()) * self.buckets.step;
+            let sketch = self
+                .steps
+                .iter()
+                .fold(Array1::zeros(self.k), |sum, s| sum + &s.sketch);
+            let attribution = self.heavy_hitters.map(|_| self.attribution(&sketch));
+            self.n_closed += 1;
+            let n = self.n_closed as f32;
+            for i in 0..self.k {
+                let delta = sketch[i] - self.mean[i];
+                self.mean[i] += delta / n;
+                self.m2[i] += delta * (sketch[i] - self.mean[i]);
+            }
+            self.out.push_back((start, sketch, attribution));
+            self.steps.pop_front();
+        }
+        self.steps.push_back(self.new_step());
+        self.step = Some(step.saturating_add(1));
+        self.src_cache.clear();
+        self.dst_cache.clear();
+    }
+
+    fn add_edge(&mut self, src: S, dst: D, w: f32) {
+        let src_pickers = &self.src_pickers;
+        let dst_pickers = &self.dst_pickers;
+        let src_in = self.src_cache.entry(src.clone()).or_insert_with_key(|src| {
+            (0..src_pickers.len())
+                .filter(|&i| src_pickers[i].picks(src))
+                .collect()
+        });
+        let dst_in = self.dst_cache.entry(dst.clone()).or_insert_with_key(|dst| {
+            (0..dst_pickers.len())
+                .filter(|&i| dst_pickers[i].picks(dst))
+                .collect()
+        });
+        let step = self.steps.back_mut().unwrap();
+        let tracks = self.heavy_hitters.is_some();
+        let (mut a, mut b) = (0, 0);
+        while a < src_in.len() && b < dst_in.len() {
+            if src_in[a] == dst_in[b] {
+                let i = src_in[a];
+                step.sketch[i] += w;
+                if tracks {
+                    step.src[i].insert(&src, w);
+                    step.dst[i].insert(&dst, w);
+                }
+                a += 1;
+                b += 1;
+            } else if src_in[a] < dst_in[b] {
+                a += 1;
+            } else {
+                b += 1;
+            }
+        }
+    }
+
+    fn next_bucket(&mut self) -> Option<Bucket<S, D>>
+    where
+        I: Iterator<Item = (u64, S, D, f32)>,
+    {
+        loop {
+            if let Some(item) = self.out.pop_front() {
+                return Some(item);
+            }
+            if self.done {
+                return None;
+            }
+            match self.iter.next() {
+                None => {
+                    self.done = true;
+                    if self.step.is_some() {
+                        self.close_step();
+                    }
+                }
+                Some((t, src, dst, w)) => {
+                    let step = t / self.buckets.step;
+                    match self.step {
+                        None => {
+                            self.step = Some(step);
+                            self.steps.push_back(self.new_step());
+                        }
+                        // a late edge goes to the current step
+                        Some(current) if step <= current => {}
+                        Some(current) => {
+                            // once the gap covers a whole bucket, the buckets left are empty
+                            (current..step)
+                                .take(self.n_steps() as usize)
+                                .for_each(|_| self.close_step());
+                            self.step = Some(step);
+                        }
+                    }
+                    self.add_edge(src, dst, w);
+                }
+            }
+        }
+    }
+}
+
+impl<I, S, D> Iterator for EdgeSpotLight<I, S, D>
+where
+    S: Hash + Eq + Clone,
+    D: Hash + Eq + Clone,
+    I: Iterator<Item = (u64, S, D, f32)>,
+{
+    type Item = (u64, Array1<f32>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_bucket().map(|(t, sketch, _)| (t, sketch))
+    }
+}
+
+pub struct AttributedEdgeSpotLight<I, S, D>(EdgeSpotLight<I, S, D>);
+
+impl<I, S, D> Iterator for AttributedEdgeSpotLight<I, S, D>
+where
+    S: Hash + Eq + Clone,
+    D: Hash + Eq + Clone,
+    I: Iterator<Item = (u64, S, D, f32)>,
+{
+    type Item = (u64, Array1<f32>, Attribution<S, D>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0
+            .next_bucket()
+            .map(|(t, sketch, attribution)| (t, sketch, attribution.unwrap()))
+    }
+}
+
+pub trait EdgeSpotLightIter<S: Hash + Eq + Clone, D: Hash + Eq + Clone>:
+    Iterator<Item = (u64, S, D, f32)> + Sized
+{
+    // Yields the start of each bucket with its sketch once the bucket is closed. The first
+    // bucket starts with the step of the first edge, and the empty buckets of gaps longer than a
+    // bucket are skipped. Edges older than the current step are counted in the current step
+    // rather than dropped. At the end of the stream the last bucket is the one ending with the
+    // step of the last edge; the sliding buckets after it would be partial and are not emitted.
+    fn spotlight_edges(
+        self,
+        cfg: &SpotLightConfig,
+        buckets: &BucketConfig,
+    ) -> EdgeSpotLight<Self, S, D> {
+        EdgeSpotLight::new(self, cfg, buckets)
+    }
+
+    fn spotlight_edges_attributed(
+        self,
+        cfg: &SpotLightConfig,
+        buckets: &BucketConfig,
+    ) -> AttributedEdgeSpotLight<Self, S, D> {
+        assert!(cfg.heavy_hitters.is_some(), "heavy hitters not tracked");
+        AttributedEdgeSpotLight(EdgeSpotLight::new(self, cfg, buckets))
+    }
+}
+
+impl<S, D, I> EdgeSpotLightIter<S, D> for I
+where
+    S: Hash + Eq + Clone,
+    D: Hash + Eq + Clone,
+    I: Iterator<Item = (u64, S, D, f32)>,
+{
+}
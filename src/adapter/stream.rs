@@ -0,0 +1,135 @@
+use std::{
+    hash::Hash,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+
+use futures::{ready, Stream, StreamExt};
+use ndarray::{ArrayBase, Data, Ix1};
+
+use crate::{algorithm::config::Config, score::PathLength};
+
+use super::{
+    prelude::*,
+    rsf_reservoir::RSFReservoir,
+    rsf_split::RSFSplit,
+    rsf_window::RSFWindow,
+    shingle::{MultiShingle, Shingle},
+    spotlight::SpotLight,
+    transform::Transform,
+};
+
+// Hands the items of the stream one at a time to an iterator adapter.
+pub struct Feed<T>(Arc<Mutex<Option<T>>>);
+
+impl<T> Iterator for Feed<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.lock().unwrap().take()
+    }
+}
+
+// The iterator adapters never fuse and pull their input lazily, so once the feed runs dry they
+// just return `None` and are polled again when the next item of the stream comes in. Adapters
+// that buffer ahead or fuse their input would lose items here.
+pub struct Adapted<St: Stream, A> {
+    stream: St,
+    feed: Arc<Mutex<Option<St::Item>>>,
+    adapter: A,
+    done: bool,
+}
+
+impl<St, A> Stream for Adapted<St, A>
+where
+    St: Stream + Unpin,
+    A: Iterator + Unpin,
+{
+    type Item = A::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            if let Some(out) = this.adapter.next() {
+                return Poll::Ready(Some(out));
+            }
+            if this.done {
+                return Poll::Ready(None);
+            }
+            match ready!(this.stream.poll_next_unpin(cx)) {
+                Some(item) => *this.feed.lock().unwrap() = Some(item),
+                None => this.done = true,
+            }
+        }
+    }
+}
+
+pub trait AdaptStream: Stream + Sized {
+    fn adapt<A, F>(self, adapter: F) -> Adapted<Self, A>
+    where
+        F: FnOnce(Feed<Self::Item>) -> A,
+    {
+        let feed = Arc::new(Mutex::new(None));
+        let adapter = adapter(Feed(feed.clone()));
+        Adapted {
+            stream: self,
+            feed,
+            adapter,
+            done: false,
+        }
+    }
+}
+
+impl<St: Stream> AdaptStream for St {}
+
+pub trait RSFStream<S: Data<Elem = f32>>: Stream<Item = ArrayBase<S, Ix1>> + Sized {
+    fn rsf_split(self, cfg: &Config) -> Adapted<Self, RSFSplit<Feed<Self::Item>>> {
+        self.adapt(|feed| feed.rsf_split(cfg))
+    }
+
+    fn rsf_window<const M: bool>(
+        self,
+        cfg: &Config,
+    ) -> Adapted<Self, RSFWindow<Feed<Self::Item>, M>> {
+        self.adapt(|feed| feed.rsf_window::<M>(cfg))
+    }
+
+    fn rsf_reservoir<const M: bool>(
+        self,
+        cfg: &Config,
+    ) -> Adapted<Self, RSFReservoir<Feed<Self::Item>, M>> {
+        self.adapt(|feed| feed.rsf_reservoir::<M>(cfg))
+    }
+
+    fn shingle(self, s: usize) -> Adapted<Self, Shingle<Feed<Self::Item>>> {
+        self.adapt(|feed| feed.shingle(s))
+    }
+
+    fn multi_shingle(self, cfg: &ShingleConfig) -> Adapted<Self, MultiShingle<Feed<Self::Item>>> {
+        self.adapt(|feed| feed.multi_shingle(cfg))
+    }
+}
+
+impl<S, St> RSFStream<S> for St
+where
+    S: Data<Elem = f32>,
+    St: Stream<Item = ArrayBase<S, Ix1>>,
+{
+}
+
+pub trait TransformStream: Stream<Item = PathLength> + Sized {
+    fn transform(self, cfg: &Config) -> Adapted<Self, Transform<Feed<Self::Item>>> {
+        self.adapt(|feed| feed.transform(cfg))
+    }
+}
+
+impl<St: Stream<Item = PathLength>> TransformStream for St {}
+
+pub trait SpotLightStream<S: Hash, D: Hash>: Stream<Item = Graph<S, D>> + Sized {
+    fn spotlight(self, cfg: &SpotLightConfig) -> Adapted<Self, SpotLight<Feed<Self::Item>>> {
+        self.adapt(|feed| feed.spotlight(cfg))
+    }
+}
+
+impl<S: Hash, D: Hash, St: Stream<Item = Graph<S, D>>> SpotLightStream<S, D> for St {}
@@ -0,0 +1,291 @@
+use std::collections::VecDeque;
+
+use crate::score::{AnomalyScore, Orientation, Score};
+
+#[derive(Clone)]
+pub struct SpotConfig {
+    pub q: f64,
+    pub level: f64,
+    pub n_init: usize,
+    pub depth: Option<usize>,
+}
+
+impl SpotConfig {
+    pub fn new(q: f64, n_init: usize) -> Self {
+        assert!(0. < q && q < 1., "invalid risk level");
+        assert!(n_init > 0, "invalid number of calibration points");
+        Self {
+            q,
+            level: 0.98,
+            n_init,
+            depth: None,
+        }
+    }
+
+    pub fn level(mut self, level: f64) -> Self {
+        assert!(0. < level && level < 1., "invalid initial quantile level");
+        self.level = level;
+        self
+    }
+
+    pub fn depth(mut self, depth: usize) -> Self {
+        assert!(depth > 0, "invalid drift depth");
+        self.depth = Some(depth);
+        self
+    }
+}
+
+// Generalised Pareto tail above the initial threshold `t`. Unlike the maximum likelihood fit of
+// the original SPOT, the shape and scale are method-of-moments estimates, which are cheap to
+// update on every peak but only hold for shapes below 1/2.
+struct Tail {
+    q: f64,
+    t: f64,
+    n: usize,
+    n_peaks: usize,
+    sum: f64,
+    sum_sq: f64,
+    gamma: f64,
+    sigma: f64,
+    z: f64,
+    bulk: Vec<f64>,
+}
+
+impl Tail {
+    fn fit(mut init: Vec<f64>, level: f64, q: f64) -> Self {
+        init.sort_unstable_by(f64::total_cmp);
+        let n = init.len();
+        let t = init[((n - 1) as f64 * level).round() as usize];
+        let mut tail = Self {
+            q,
+            t,
+            n,
+            n_peaks: 0,
+            sum: 0.,
+            sum_sq: 0.,
+            gamma: 0.,
+            sigma: 0.,
+            z: t,
+            bulk: init,
+        };
+        let peaks: Vec<_> = tail
+            .bulk
+            .iter()
+            .map(|x| x - t)
+            .filter(|&y| y > 0.)
+            .collect();
+        peaks.into_iter().for_each(|y| tail.add_peak(y));
+        tail.refit();
+        tail
+    }
+
+    fn add_peak(&mut self, y: f64) {
+        self.n_peaks += 1;
+        self.sum += y;
+        self.sum_sq += y * y;
+    }
+
+    fn refit(&mut self) {
+        if self.n_peaks == 0 {
+            self.gamma = 0.;
+            self.sigma = 0.;
+            self.z = self.t;
+            return;
+        }
+        let k = self.n_peaks as f64;
+        let mean = self.sum / k;
+        let var = self.sum_sq / k - mean * mean;
+        if var <= f64::EPSILON {
+            self.gamma = 0.;
+            self.sigma = mean;
+        } else {
+            let r = mean * mean / var;
+            self.gamma = 0.5 * (1. - r);
+            self.sigma = 0.5 * mean * (r + 1.);
+        }
+        let ratio = self.q * (self.n as f64) / k;
+        self.z = if self.gamma.abs() < 1e-8 {
+            self.t - self.sigma * ratio.ln()
+        } else {
+            self.t + self.sigma / self.gamma * (ratio.powf(-self.gamma) - 1.)
+        };
+    }
+
+    fn survival(&self, y: f64) -> f64 {
+        if self.sigma == 0. {
+            0.
+        } else if self.gamma.abs() < 1e-8 {
+            (-y / self.sigma).exp()
+        } else {
+            let base = 1. + self.gamma * y / self.sigma;
+            if base <= 0. {
+                0.
+            } else {
+                base.powf(-self.gamma.recip())
+            }
+        }
+    }
+
+    fn exceedance(&self, x: f64) -> f64 {
+        if x > self.t {
+            (self.n_peaks as f64) / (self.n as f64) * self.survival(x - self.t)
+        } else {
+            let below = self.bulk.partition_point(|&v| v < x);
+            ((self.bulk.len() - below) as f64) / (self.bulk.len() as f64)
+        }
+    }
+
+    fn update(&mut self, x: f64) -> bool {
+        if x > self.z {
+            return true;
+        }
+        self.n += 1;
+        if x > self.t {
+            self.add_peak(x - self.t);
+            self.refit();
+        }
+        false
+    }
+}
+
+struct Drift {
+    depth: usize,
+    buf: VecDeque<f64>,
+    sum: f64,
+}
+
+impl Drift {
+    fn new(depth: usize) -> Self {
+        Self {
+            depth,
+            buf: VecDeque::with_capacity(depth),
+            sum: 0.,
+        }
+    }
+
+    fn is_full(&self) -> bool {
+        self.buf.len() == self.depth
+    }
+
+    fn mean(&self) -> f64 {
+        if self.buf.is_empty() {
+            0.
+        } else {
+            self.sum / (self.buf.len() as f64)
+        }
+    }
+
+    fn push(&mut self, x: f64) {
+        if self.is_full() {
+            self.sum -= self.buf.pop_front().unwrap();
+        }
+        self.buf.push_back(x);
+        self.sum += x;
+    }
+}
+
+// The first `depth` scores fill the drift window and the next `n_init` calibrate the tail; no
+// alarm is emitted for them, so streams shorter than that emit nothing.
+pub struct Spot<I> {
+    iter: I,
+    cfg: SpotConfig,
+    init: Vec<f64>,
+    tail: Option<Tail>,
+    drift: Option<Drift>,
+}
+
+impl<O: Orientation, I: Iterator<Item = Score<O>>> Spot<I> {
+    fn new(iter: I, cfg: &SpotConfig) -> Self {
+        Self {
+            iter,
+            cfg: cfg.clone(),
+            init: Vec::with_capacity(cfg.n_init),
+            tail: None,
+            drift: cfg.depth.map(Drift::new),
+        }
+    }
+
+    // None until the tail is calibrated.
+    pub fn threshold(&self) -> Option<Score<O>> {
+        let offset = self.drift.as_ref().map_or(0., |d| d.mean());
+        let tail = self.tail.as_ref()?;
+        Some(Score::from_anomaly((tail.z + offset) as f32))
+    }
+
+    fn step(&mut self, x: Score<O>) -> Option<(bool, f64)> {
+        let x = x.anomaly() as f64;
+        let y = match self.drift.as_mut() {
+            Some(drift) if !drift.is_full() => {
+                drift.push(x);
+                return None;
+            }
+            Some(drift) => x - drift.mean(),
+            None => x,
+        };
+        let Some(tail) = self.tail.as_mut() else {
+            if let Some(drift) = self.drift.as_mut() {
+                drift.push(x);
+            }
+            self.init.push(y);
+            if self.init.len() == self.cfg.n_init {
+                let init = std::mem::take(&mut self.init);
+                self.tail = Some(Tail::fit(init, self.cfg.level, self.cfg.q));
+            }
+            return None;
+        };
+        let p = tail.exceedance(y);
+        let alarm = tail.update(y);
+        if !alarm {
+            if let Some(drift) = self.drift.as_mut() {
+                drift.push(x);
+            }
+        }
+        Some((alarm, p))
+    }
+}
+
+impl<O: Orientation, I: Iterator<Item = Score<O>>> Iterator for Spot<I> {
+    type Item = bool;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let x = self.iter.next()?;
+            if let Some((alarm, _)) = self.step(x) {
+                return Some(alarm);
+            }
+        }
+    }
+}
+
+pub struct SpotProba<I>(Spot<I>);
+
+impl<O: Orientation, I: Iterator<Item = Score<O>>> SpotProba<I> {
+    pub fn threshold(&self) -> Option<Score<O>> {
+        self.0.threshold()
+    }
+}
+
+impl<O: Orientation, I: Iterator<Item = Score<O>>> Iterator for SpotProba<I> {
+    type Item = AnomalyScore;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let x = self.0.iter.next()?;
+            if let Some((_, p)) = self.0.step(x) {
+                return Some(AnomalyScore::new((1. - p) as f32));
+            }
+        }
+    }
+}
+
+pub trait ThresholdIter<O: Orientation>: Iterator<Item = Score<O>> + Sized {
+    fn spot(self, cfg: &SpotConfig) -> Spot<Self> {
+        Spot::new(self, cfg)
+    }
+
+    fn spot_proba(self, cfg: &SpotConfig) -> SpotProba<Self> {
+        SpotProba(Spot::new(self, cfg))
+    }
+}
+
+impl<O: Orientation, I: Iterator<Item = Score<O>>> ThresholdIter<O> for I {}
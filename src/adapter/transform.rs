@@ -1,4 +1,7 @@
-use crate::algorithm::config::Config;
+use crate::{
+    algorithm::config::Config,
+    score::{AnomalyScore, PathLength},
+};
 
 pub struct Transform<I> {
     iter: I,
@@ -14,18 +17,20 @@ impl<I> Transform<I> {
     }
 }
 
-impl<I: Iterator<Item = f32>> Iterator for Transform<I> {
-    type Item = f32;
+impl<I: Iterator<Item = PathLength>> Iterator for Transform<I> {
+    type Item = AnomalyScore;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.iter.next().map(|s| (-s / self.cn).exp2())
+        self.iter
+            .next()
+            .map(|s| AnomalyScore::new((-s.value() / self.cn).exp2()))
     }
 }
 
-pub trait TransformIter: Iterator<Item = f32> + Sized {
+pub trait TransformIter: Iterator<Item = PathLength> + Sized {
     fn transform(self, cfg: &Config) -> Transform<Self> {
         Transform::new(self, cfg)
     }
 }
 
-impl<I: Iterator<Item = f32>> TransformIter for I {}
+impl<I: Iterator<Item = PathLength>> TransformIter for I {}
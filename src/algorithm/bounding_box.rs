@@ -94,6 +94,14 @@ impl BoundingBox {
             .all(|(bound, &coord)| bound[0] <= coord && coord <= bound[1])
     }
 
+    // Whether the interiors intersect. Two cells of a tree either nest or only share a boundary.
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.bounds
+            .outer_iter()
+            .zip(other.bounds.outer_iter())
+            .all(|(a, b)| a[0] < b[1] && b[0] < a[1])
+    }
+
     pub fn contains_at(&self, p: &Array1<f32>, dim: usize) -> bool {
         self.bounds[(dim, 0)] <= p[dim] && p[dim] <= self.bounds[(dim, 1)]
     }
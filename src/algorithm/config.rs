@@ -1,7 +1,99 @@
+use ndarray::{concatenate, stack, Axis};
 use rand::{prelude::StdRng, thread_rng, SeedableRng};
 
+use crate::period::detect_period;
+
 use super::bounding_box::BoundingBox;
 
+// Order in which the streaming detectors score a point and add it to their sample. With
+// `Delayed(d)` a point is only added after `d` further points, so a burst of anomalies is
+// scored against the model from before the burst.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UpdateMode {
+    ScoreThenUpdate,
+    UpdateThenScore,
+    Delayed(usize),
+}
+
+// How the reservoirs of `rsf_reservoir` sample the stream. `Biased` follows Aggarwal's
+// exponentially biased reservoir and `Priority` keeps the points of smallest exponentially
+// decayed priority; both forget old points with the given half-life, in number of points.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Sampling {
+    Uniform,
+    Biased(f64),
+    Priority(f64),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShingleTransform {
+    Raw,
+    Diff,
+    Ratio,
+}
+
+// The current item is followed by the items `lags` steps back, and only every `stride`-th
+// shingle is emitted. With `Diff` or `Ratio` the lagged items are replaced by the difference
+// or ratio of the current item to them.
+#[derive(Clone, Debug)]
+pub struct ShingleConfig {
+    pub lags: Vec<usize>,
+    pub stride: usize,
+    pub transform: ShingleTransform,
+}
+
+impl ShingleConfig {
+    pub fn new(mut lags: Vec<usize>) -> Self {
+        lags.sort_unstable();
+        lags.dedup();
+        assert!(lags.iter().all(|&l| l > 0), "invalid lag");
+        Self {
+            lags,
+            stride: 1,
+            transform: ShingleTransform::Raw,
+        }
+    }
+
+    pub fn consecutive(s: usize) -> Self {
+        assert!(s > 0, "invalid shingle size");
+        Self::new((1..s).collect())
+    }
+
+    pub fn stride(mut self, stride: usize) -> Self {
+        assert!(stride > 0, "invalid stride");
+        self.stride = stride;
+        self
+    }
+
+    pub fn transform(mut self, transform: ShingleTransform) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    pub fn max_lag(&self) -> usize {
+        self.lags.last().copied().unwrap_or(0)
+    }
+
+    // Bounding box of the shingles of points within `bb`.
+    pub fn bounding_box(&self, bb: &BoundingBox) -> BoundingBox {
+        let (lb, ub) = (bb.bounds.column(0), bb.bounds.column(1));
+        let lagged = match self.transform {
+            ShingleTransform::Raw => bb.bounds.clone(),
+            ShingleTransform::Diff => stack![Axis(1), &lb - &ub, &ub - &lb],
+            ShingleTransform::Ratio => {
+                assert!(
+                    lb.iter().all(|&v| v > 0.),
+                    "ratio shingles need strictly positive lower bounds"
+                );
+                stack![Axis(1), &lb / &ub, &ub / &lb]
+            }
+        };
+        let mut parts = vec![bb.bounds.view()];
+        parts.extend(self.lags.iter().map(|_| lagged.view()));
+        BoundingBox::new(concatenate(Axis(0), &parts).unwrap())
+    }
+}
+
 #[derive(Clone)]
 pub struct Config {
     pub bb: BoundingBox,
@@ -13,6 +105,9 @@ pub struct Config {
     pub seed: Option<u64>,
     pub sketch_size: usize,
     pub n_machines: usize,
+    pub update: UpdateMode,
+    pub sampling: Sampling,
+    pub feedback_horizon: usize,
 }
 
 impl Config {
@@ -40,10 +135,14 @@ pub struct ConfigBuilder {
     n_points: Option<usize>,
     granularity: Option<usize>,
     shingle: Option<usize>,
+    multi_shingle: Option<ShingleConfig>,
     window: Option<usize>,
     seed: Option<u64>,
     sketch_size: Option<usize>,
     n_machines: Option<usize>,
+    update: Option<UpdateMode>,
+    sampling: Option<Sampling>,
+    feedback_horizon: Option<usize>,
 }
 
 impl ConfigBuilder {
@@ -69,6 +168,20 @@ impl ConfigBuilder {
 
     pub fn shingle(mut self, shingle: usize) -> Self {
         self.shingle = Some(shingle);
+        self.multi_shingle = None;
+        self
+    }
+
+    // Shingles by the dominant period of `values`, or not at all if there is none.
+    pub fn auto_shingle(self, values: &[f32]) -> Self {
+        self.shingle(detect_period(values).unwrap_or(1))
+    }
+
+    // For points shingled by `multi_shingle`, whose bounding box depends on the lags and the
+    // transform.
+    pub fn multi_shingle(mut self, shingle_cfg: &ShingleConfig) -> Self {
+        self.shingle = None;
+        self.multi_shingle = Some(shingle_cfg.clone());
         self
     }
 
@@ -88,26 +201,59 @@ impl ConfigBuilder {
     }
 
     pub fn n_machines(mut self, n_machines: usize) -> Self {
+        assert!(n_machines > 0, "no machines");
         self.n_machines = Some(n_machines);
         self
     }
 
+    pub fn update(mut self, update: UpdateMode) -> Self {
+        self.update = Some(update);
+        self
+    }
+
+    pub fn sampling(mut self, sampling: Sampling) -> Self {
+        if let Sampling::Biased(half_life) | Sampling::Priority(half_life) = sampling {
+            assert!(half_life > 0., "invalid half-life");
+        }
+        self.sampling = Some(sampling);
+        self
+    }
+
+    // Number of points after which the feedback given to a streaming detector expires. Defaults
+    // to the window, which is also when a window detector forgets the point itself.
+    pub fn feedback_horizon(mut self, feedback_horizon: usize) -> Self {
+        assert!(feedback_horizon > 0, "invalid feedback horizon");
+        self.feedback_horizon = Some(feedback_horizon);
+        self
+    }
+
     pub fn build(&self) -> Config {
-        let shingle = self.shingle.unwrap_or(1);
+        let shingle_cfg = self
+            .multi_shingle
+            .clone()
+            .unwrap_or_else(|| ShingleConfig::consecutive(self.shingle.unwrap_or(1)));
+        let shingle = shingle_cfg.lags.len() + 1;
         let n_points = self.n_points.unwrap_or(128);
-        let mut bb = self.bounding_box.clone().expect("no bounding box provided");
-        bb.shingle(shingle);
+        let window = self.window.unwrap_or(n_points);
+        let bb = self
+            .bounding_box
+            .as_ref()
+            .expect("no bounding box provided");
+        let bb = shingle_cfg.bounding_box(bb);
 
         Config {
             bb,
             n_trees: self.n_trees.unwrap_or(64),
             n_points,
             granularity: self.granularity.unwrap_or(1),
-            window: self.window.unwrap_or(n_points),
+            window,
             shingle,
             seed: self.seed,
             sketch_size: self.sketch_size.unwrap_or(2),
             n_machines: self.n_machines.unwrap_or(2),
+            update: self.update.unwrap_or(UpdateMode::ScoreThenUpdate),
+            sampling: self.sampling.unwrap_or(Sampling::Uniform),
+            feedback_horizon: self.feedback_horizon.unwrap_or(window),
         }
     }
 }
@@ -1,17 +1,34 @@
 use std::{
+    collections::VecDeque,
     ops::{Index, IndexMut},
     slice::IterMut,
 };
 
-use ndarray::{Array1, ArrayBase, Data, Ix1, Ix2};
+use ndarray::{Array1, ArrayBase, Axis, Data, Ix1, Ix2, Zip};
+
+use crate::score::PathLength;
 
 use super::{
+    bounding_box::BoundingBox,
     config::Config,
+    node::RandShiftNode,
     tree::{RandShiftTree, RSQT, RST},
 };
 
+// Every damped cell is checked on every score, so only the latest ones are kept.
+pub const MAX_DAMPED: usize = 64;
+
+// A damped point, by the cell it fell in in each tree when it was damped.
+#[derive(Clone)]
+struct Damped {
+    cells: Vec<BoundingBox>,
+    factor: f32,
+}
+
+#[derive(Clone)]
 pub struct RandShiftForest<T: RandShiftTree> {
     trees: Vec<T>,
+    damped: VecDeque<Damped>,
 }
 
 impl<T: RandShiftTree> RandShiftForest<T> {
@@ -20,7 +37,10 @@ impl<T: RandShiftTree> RandShiftForest<T> {
         let trees = (0..cfg.n_trees)
             .map(|i| T::from_config(cfg, i, &mut rng))
             .collect::<Vec<_>>();
-        Self { trees }
+        Self {
+            trees,
+            damped: VecDeque::new(),
+        }
     }
 
     pub fn n_trees(&self) -> usize {
@@ -43,8 +63,51 @@ impl<T: RandShiftTree> RandShiftForest<T> {
         self.trees.iter_mut().for_each(|t| t.remove(p));
     }
 
-    pub fn score<S: Data<Elem = f32>>(&self, p: &ArrayBase<S, Ix1>) -> f32 {
-        self.trees.iter().map(|t| t.score(p)).sum::<f32>() / (self.n_trees() as f32)
+    // Inserts a point labelled as normal as if it had been seen `weight` times.
+    pub fn insert_weighted<S: Data<Elem = f32>>(&mut self, p: &ArrayBase<S, Ix1>, weight: usize) {
+        (0..weight).for_each(|_| self.insert(p));
+    }
+
+    // Path lengths in the cells `p` falls in now are multiplied by `factor`, in every tree and
+    // whatever happens to the points of the forest afterwards, until `MAX_DAMPED` later points
+    // are damped or it is expired with `expire_damped`. Leaves that later split or merge are
+    // damped wherever they overlap the recorded cell, so damping an empty tree damps all of it.
+    pub fn damp<S: Data<Elem = f32>>(&mut self, p: &ArrayBase<S, Ix1>, factor: f32) {
+        assert!(factor >= 1., "invalid damping factor");
+        let cells = self.trees.iter().map(|t| t.leaf(p).bb().clone()).collect();
+        self.damped.push_back(Damped { cells, factor });
+        if self.damped.len() > MAX_DAMPED {
+            self.damped.pop_front();
+        }
+    }
+
+    // Drops the oldest damped point.
+    pub fn expire_damped(&mut self) {
+        self.damped.pop_front();
+    }
+
+    pub fn clear_damped(&mut self) {
+        self.damped.clear();
+    }
+
+    // Score of `p` in the `i`-th tree, with a single traversal whether it is damped or not.
+    fn tree_score<S: Data<Elem = f32>>(&self, i: usize, p: &ArrayBase<S, Ix1>) -> f32 {
+        let t = &self.trees[i];
+        let leaf = t.leaf(p);
+        let damping = self
+            .damped
+            .iter()
+            .filter(|damped| damped.cells[i].overlaps(leaf.bb()))
+            .map(|damped| damped.factor)
+            .fold(1., f32::max);
+        t.leaf_score(leaf).value() * damping
+    }
+
+    pub fn score<S: Data<Elem = f32>>(&self, p: &ArrayBase<S, Ix1>) -> PathLength {
+        let sum = (0..self.n_trees())
+            .map(|i| self.tree_score(i, p))
+            .sum::<f32>();
+        PathLength::new(sum / (self.n_trees() as f32))
     }
 
     pub fn n_points(&self) -> f32 {
@@ -52,21 +115,61 @@ impl<T: RandShiftTree> RandShiftForest<T> {
         (point_sum as f32) / (self.n_trees() as f32)
     }
 
-    pub fn batch_score<S: Data<Elem = f32>>(&self, ps: &ArrayBase<S, Ix2>) -> Array1<f32> {
+    pub fn weight(&self) -> f32 {
+        let weight_sum: usize = self.trees.iter().map(|t| t.weight()).sum();
+        (weight_sum as f32) / (self.n_trees() as f32)
+    }
+
+    pub fn batch_score<S: Data<Elem = f32>>(&self, ps: &ArrayBase<S, Ix2>) -> Array1<PathLength> {
         let zero = Array1::from_elem(ps.dim().0, 0.);
-        let sum = self
-            .trees
-            .iter()
-            .map(|t| t.batch_score(ps))
+        let sum = (0..self.n_trees())
+            .map(|i| {
+                if self.damped.is_empty() {
+                    self.trees[i].batch_score(ps).mapv(PathLength::value)
+                } else {
+                    let mut scores = Array1::zeros(ps.dim().0);
+                    Zip::from(&mut scores)
+                        .and(ps.axis_iter(Axis(0)))
+                        .for_each(|s, p| *s = self.tree_score(i, &p));
+                    scores
+                }
+            })
             .fold(zero, |sum, scores| sum + scores);
-        sum / (self.n_trees() as f32)
+        let n_trees = self.n_trees() as f32;
+        sum.mapv(|s| PathLength::new(s / n_trees))
     }
 
     pub fn sketch(&mut self, sketch_size: usize) {
         self.trees.iter_mut().for_each(|t| t.sketch(sketch_size));
     }
 
+    // Points of every tree, which are enough to rebuild the forest from the same config.
+    pub fn points(&self) -> Vec<Vec<(Array1<f32>, usize)>> {
+        self.trees.iter().map(|t| t.points()).collect()
+    }
+
+    pub fn insert_points(&mut self, points: &[Vec<(Array1<f32>, usize)>]) {
+        assert_eq!(points.len(), self.n_trees(), "invalid number of trees");
+        self.trees
+            .iter_mut()
+            .zip(points)
+            .for_each(|(t, points)| t.insert_points(points));
+    }
+
+    // Takes out points previously given to `insert_points`.
+    pub fn remove_points(&mut self, points: &[Vec<(Array1<f32>, usize)>]) {
+        assert_eq!(points.len(), self.n_trees(), "invalid number of trees");
+        self.trees
+            .iter_mut()
+            .zip(points)
+            .for_each(|(t, points)| t.remove_points(points));
+    }
+
     pub fn extend(&mut self, other: Self) {
+        self.damped.extend(other.damped);
+        while self.damped.len() > MAX_DAMPED {
+            self.damped.pop_front();
+        }
         self.trees
             .iter_mut()
             .zip(other.trees.into_iter())
@@ -108,6 +108,7 @@ where
     }
 }
 
+#[derive(Clone)]
 pub struct RSQTNode {
     bb: BoundingBox,
     children: Vec<RSQTNode>,
@@ -185,6 +186,7 @@ impl RandShiftNode for RSQTNode {
     }
 }
 
+#[derive(Clone)]
 pub struct RSTNode {
     bb: BoundingBox,
     children: Vec<RSTNode>,
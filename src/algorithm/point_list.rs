@@ -4,6 +4,7 @@ use ndarray::Array1;
 
 use super::bounding_box::BoundingBox;
 
+#[derive(Clone)]
 pub struct Point {
     pub coords: Array1<f32>,
     pub weight: usize,
@@ -15,6 +16,7 @@ impl Point {
     }
 }
 
+#[derive(Clone)]
 pub struct PointList(pub Vec<Point>);
 
 impl PointList {
@@ -1,4 +1,5 @@
 pub use super::{
     bounding_box::BoundingBox, bounding_box::BoundingBoxIter, config::Config,
-    config::ConfigBuilder, forest::RandShiftForest, forest::RSF, forest::RSQF, tree::RandShiftTree,
+    config::ConfigBuilder, config::Sampling, config::UpdateMode, forest::RandShiftForest,
+    forest::RSF, forest::RSQF, tree::RandShiftTree,
 };
@@ -7,7 +7,7 @@ use plotly::{
 };
 use rand::Rng;
 
-use crate::metric::exp_bst_path_length;
+use crate::{metric::exp_bst_path_length, score::PathLength};
 
 use super::{
     bounding_box::BoundingBox,
@@ -39,23 +39,42 @@ where
         self.node_iter().map(|node| node.n_points()).sum()
     }
 
+    fn weight(&self) -> usize {
+        self.node_iter().map(|node| node.weight()).sum()
+    }
+
     fn batch_insert<S: Data<Elem = f32>>(&mut self, x: &ArrayBase<S, Ix2>) {
         x.outer_iter().for_each(|p| self.insert(&p));
     }
 
-    fn score<S: Data<Elem = f32>>(&self, p: &ArrayBase<S, Ix1>) -> f32 {
-        let p_shift = p + self.shift();
-        let node = self.root().find(&p_shift);
+    // Leaf whose cell contains `p`.
+    fn leaf<S: Data<Elem = f32>>(&self, p: &ArrayBase<S, Ix1>) -> &Self::Node {
+        self.root().find(&(p + self.shift()))
+    }
+
+    fn score<S: Data<Elem = f32>>(&self, p: &ArrayBase<S, Ix1>) -> PathLength {
+        self.leaf_score(self.leaf(p))
+    }
+
+    fn leaf_score(&self, node: &Self::Node) -> PathLength {
         if node.depth() == self.max_depth() {
             let weight = node.weight();
             if weight > self.max_points() {
-                return (node.path_length() as f32) + exp_bst_path_length(weight);
+                return PathLength::new((node.path_length() as f32) + exp_bst_path_length(weight));
             }
         }
-        node.path_length() as f32
+        PathLength::new(node.path_length() as f32)
     }
 
-    fn batch_score<S: Data<Elem = f32>>(&self, x: &ArrayBase<S, Ix2>) -> Array1<f32> {
+    fn same_cell<S1, S2>(&self, p: &ArrayBase<S1, Ix1>, q: &ArrayBase<S2, Ix1>) -> bool
+    where
+        S1: Data<Elem = f32>,
+        S2: Data<Elem = f32>,
+    {
+        std::ptr::eq(self.leaf(p), self.leaf(q))
+    }
+
+    fn batch_score<S: Data<Elem = f32>>(&self, x: &ArrayBase<S, Ix2>) -> Array1<PathLength> {
         x.outer_iter().map(|p| self.score(&p)).collect()
     }
 
@@ -68,17 +87,30 @@ where
         root.contract_at(&p_shift, max_points);
     }
 
-    fn extend(&mut self, other: Self) {
-        for node in other.node_iter() {
-            for point in &node.point_list().0 {
-                let unshifted = &point.coords - self.shift();
-                repeat(unshifted.view())
-                    .take(point.weight)
-                    .for_each(|p| self.insert(&p));
-            }
+    // Unshifted points of the tree with their weights.
+    fn points(&self) -> Vec<(Array1<f32>, usize)> {
+        self.node_iter()
+            .flat_map(|node| node.point_list().0.iter())
+            .map(|point| (&point.coords - self.shift(), point.weight))
+            .collect()
+    }
+
+    fn insert_points(&mut self, points: &[(Array1<f32>, usize)]) {
+        for (p, weight) in points {
+            repeat(p.view()).take(*weight).for_each(|p| self.insert(&p));
         }
     }
 
+    fn remove_points(&mut self, points: &[(Array1<f32>, usize)]) {
+        for (p, weight) in points {
+            (0..*weight).for_each(|_| self.remove(p));
+        }
+    }
+
+    fn extend(&mut self, other: Self) {
+        self.insert_points(&other.points());
+    }
+
     fn sketch(&mut self, sketch_size: usize) {
         self.root_mut().sketch(sketch_size);
     }
@@ -107,6 +139,7 @@ impl<'a, T: RandShiftTree> Iterator for NodeIter<'a, T> {
     }
 }
 
+#[derive(Clone)]
 pub struct RST {
     max_depth: usize,
     max_points: usize,
@@ -199,6 +232,7 @@ impl RandShiftTree for RST {
     }
 }
 
+#[derive(Clone)]
 pub struct RSQT {
     max_depth: usize,
     max_points: usize,
@@ -1,6 +1,9 @@
 pub mod adapter;
 pub mod algorithm;
 pub mod metric;
+pub mod period;
 pub mod prelude;
+pub mod score;
 #[cfg(test)]
 mod tests;
+pub mod tune;
@@ -1,31 +1,38 @@
+use std::ops::Range;
+
 use classifier_measures::{pr_auc, roc_auc};
-use ndarray::{s, ArrayBase, Data, Ix1};
+use ndarray::{s, Array1, ArrayBase, Data, Ix1};
 use num_traits::Float;
 
-pub fn rocauc<F: Float, S1: Data<Elem = bool>, S2: Data<Elem = F>>(
+use crate::score::{Orientation, Score};
+
+pub fn rocauc<O: Orientation, S1: Data<Elem = bool>, S2: Data<Elem = Score<O>>>(
     y_true: &ArrayBase<S1, Ix1>,
     y_pred: &ArrayBase<S2, Ix1>,
 ) -> f64 {
     let n = y_true.len();
     let m = y_pred.len();
     let offset = n - m;
-    roc_auc(0..m, |i| (y_true[offset + i], y_pred[i]))
-        .unwrap()
-        .to_f64()
-        .unwrap()
+    roc_auc(0..m, |i| (y_true[offset + i], y_pred[i].anomaly())).unwrap() as f64
 }
 
-pub fn prauc<F: Float, S1: Data<Elem = bool>, S2: Data<Elem = F>>(
+pub fn prauc<O: Orientation, S1: Data<Elem = bool>, S2: Data<Elem = Score<O>>>(
     y_true: &ArrayBase<S1, Ix1>,
     y_pred: &ArrayBase<S2, Ix1>,
 ) -> f64 {
     let n = y_true.len();
     let m = y_pred.len();
     let offset = n - m;
-    pr_auc(0..m, |i| (y_true[offset + i], y_pred[i]))
-        .unwrap()
-        .to_f64()
-        .unwrap()
+    pr_auc(0..m, |i| (y_true[offset + i], y_pred[i].anomaly())).unwrap() as f64
+}
+
+pub fn top_k<O: Orientation, S: Data<Elem = Score<O>>>(
+    scores: &ArrayBase<S, Ix1>,
+    k: usize,
+) -> Vec<usize> {
+    let mut args = Vec::from_iter(0..scores.len());
+    args.sort_unstable_by(|&i1, &i2| scores[i2].cmp(&scores[i1]));
+    args.into_iter().take(k).collect()
 }
 
 pub fn k_smallest<F: Float, S: Data<Elem = F>>(arr: &ArrayBase<S, Ix1>, k: usize) -> Vec<usize> {
@@ -40,14 +47,14 @@ pub fn k_largest<F: Float, S: Data<Elem = F>>(arr: &ArrayBase<S, Ix1>, k: usize)
     max_args.into_iter().take(k).collect()
 }
 
-pub fn pr_n1<F: Float, S1: Data<Elem = bool>, S2: Data<Elem = F>>(
+pub fn pr_n1<O: Orientation, S1: Data<Elem = bool>, S2: Data<Elem = Score<O>>>(
     y_true: &ArrayBase<S1, Ix1>,
     y_pred: &ArrayBase<S2, Ix1>,
 ) -> f64 {
     let m = y_pred.len() as isize;
     let y_true = y_true.slice(s![-m..]);
     let n1 = y_true.iter().filter(|&&anomaly| anomaly).count();
-    let anomalies = k_largest(y_pred, n1);
+    let anomalies = top_k(y_pred, n1);
     pr(&y_true, &anomalies)
 }
 
@@ -63,6 +70,269 @@ pub fn rc<S: Data<Elem = bool>>(y_true: &ArrayBase<S, Ix1>, anomalies: &[usize])
     (tp as f64) / (n1 as f64)
 }
 
+pub fn pr_at_k<O: Orientation, S1: Data<Elem = bool>, S2: Data<Elem = Score<O>>>(
+    y_true: &ArrayBase<S1, Ix1>,
+    y_pred: &ArrayBase<S2, Ix1>,
+    k: usize,
+) -> f64 {
+    let m = y_pred.len() as isize;
+    let y_true = y_true.slice(s![-m..]);
+    let anomalies = top_k(y_pred, k);
+    pr(&y_true, &anomalies)
+}
+
+pub fn f_score(pr: f64, rc: f64, beta: f64) -> f64 {
+    let beta2 = beta * beta;
+    if pr + rc == 0. {
+        0.
+    } else {
+        (1. + beta2) * pr * rc / (beta2 * pr + rc)
+    }
+}
+
+pub fn f1_at<O: Orientation, S1: Data<Elem = bool>, S2: Data<Elem = Score<O>>>(
+    y_true: &ArrayBase<S1, Ix1>,
+    y_pred: &ArrayBase<S2, Ix1>,
+    threshold: Score<O>,
+) -> f64 {
+    let m = y_pred.len() as isize;
+    let y_true = y_true.slice(s![-m..]);
+    let anomalies: Vec<usize> = y_pred
+        .iter()
+        .enumerate()
+        .filter_map(|(i, &s)| if s >= threshold { Some(i) } else { None })
+        .collect();
+    if anomalies.is_empty() {
+        return 0.;
+    }
+    f_score(pr(&y_true, &anomalies), rc(&y_true, &anomalies), 1.)
+}
+
+/// Maximal runs of `true` values, e.g. the labelled windows of a time series.
+pub fn ranges<S: Data<Elem = bool>>(y: &ArrayBase<S, Ix1>) -> Vec<Range<usize>> {
+    let mut res = Vec::new();
+    let mut start = None;
+    for (i, &v) in y.iter().enumerate() {
+        match (v, start) {
+            (true, None) => start = Some(i),
+            (false, Some(lb)) => {
+                res.push(lb..i);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(lb) = start {
+        res.push(lb..y.len());
+    }
+    res
+}
+
+fn align<S: Data<Elem = bool>>(y_alarm: &ArrayBase<S, Ix1>, n: usize) -> Array1<bool> {
+    let offset = n
+        .checked_sub(y_alarm.len())
+        .expect("more alarms than labels");
+    (0..n).map(|i| i >= offset && y_alarm[i - offset]).collect()
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Bias {
+    Flat,
+    Front,
+    Middle,
+    Back,
+}
+
+impl Bias {
+    fn delta(&self, i: usize, len: usize) -> f64 {
+        let v = match self {
+            Bias::Flat => 1,
+            Bias::Front => len - i + 1,
+            Bias::Middle => {
+                if i <= len / 2 {
+                    i
+                } else {
+                    len - i + 1
+                }
+            }
+            Bias::Back => i,
+        };
+        v as f64
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Cardinality {
+    One,
+    Reciprocal,
+}
+
+impl Cardinality {
+    fn gamma(&self, n_overlaps: usize) -> f64 {
+        match self {
+            _ if n_overlaps <= 1 => 1.,
+            Cardinality::One => 1.,
+            Cardinality::Reciprocal => (n_overlaps as f64).recip(),
+        }
+    }
+}
+
+fn overlap_reward(
+    range: &Range<usize>,
+    others: &[Range<usize>],
+    bias: Bias,
+    cardinality: Cardinality,
+) -> f64 {
+    let overlapping: Vec<_> = others
+        .iter()
+        .filter(|o| o.start < range.end && range.start < o.end)
+        .collect();
+    let len = range.len();
+    let (mut value, mut max_value) = (0., 0.);
+    for (i, idx) in range.clone().enumerate() {
+        let delta = bias.delta(i + 1, len);
+        max_value += delta;
+        if overlapping.iter().any(|o| o.contains(&idx)) {
+            value += delta;
+        }
+    }
+    cardinality.gamma(overlapping.len()) * value / max_value
+}
+
+/// Range-based recall of Tatbul et al. (2018); `alpha` weighs the existence reward against the
+/// overlap reward. Alarms shorter than the labels are aligned to their end, as in `rocauc`.
+/// Without labelled windows there is nothing to miss and the recall is 1.
+pub fn range_rc<S1: Data<Elem = bool>, S2: Data<Elem = bool>>(
+    y_true: &ArrayBase<S1, Ix1>,
+    y_alarm: &ArrayBase<S2, Ix1>,
+    alpha: f64,
+    bias: Bias,
+    cardinality: Cardinality,
+) -> f64 {
+    let real = ranges(y_true);
+    let pred = ranges(&align(y_alarm, y_true.len()));
+    if real.is_empty() {
+        return 1.;
+    }
+    let sum: f64 = real
+        .iter()
+        .map(|r| {
+            let overlap = overlap_reward(r, &pred, bias, cardinality);
+            let existence = if overlap > 0. { 1. } else { 0. };
+            alpha * existence + (1. - alpha) * overlap
+        })
+        .sum();
+    sum / (real.len() as f64)
+}
+
+/// Range-based precision of Tatbul et al. (2018). Without alarms the precision is 0, as in
+/// `f1_at`.
+pub fn range_pr<S1: Data<Elem = bool>, S2: Data<Elem = bool>>(
+    y_true: &ArrayBase<S1, Ix1>,
+    y_alarm: &ArrayBase<S2, Ix1>,
+    bias: Bias,
+    cardinality: Cardinality,
+) -> f64 {
+    let real = ranges(y_true);
+    let pred = ranges(&align(y_alarm, y_true.len()));
+    if pred.is_empty() {
+        return 0.;
+    }
+    let sum: f64 = pred
+        .iter()
+        .map(|p| overlap_reward(p, &real, bias, cardinality))
+        .sum();
+    sum / (pred.len() as f64)
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum NabProfile {
+    Standard,
+    LowFp,
+    LowFn,
+}
+
+impl NabProfile {
+    fn weights(&self) -> (f64, f64, f64) {
+        // (tp, fp, fn)
+        match self {
+            NabProfile::Standard => (1.0, 0.11, 1.0),
+            NabProfile::LowFp => (1.0, 0.22, 1.0),
+            NabProfile::LowFn => (1.0, 0.11, 2.0),
+        }
+    }
+}
+
+fn scaled_sigmoid(y: f64) -> f64 {
+    if y > 3. {
+        -1.
+    } else {
+        2. / (1. + (5. * y).exp()) - 1.
+    }
+}
+
+/// Normalised NAB score in which 100 is a perfect detector and 0 is a detector that never
+/// fires. Only the first alarm in a labelled window is rewarded; later alarms outside of a
+/// window are penalised less the further they are from the preceding window. Without labelled
+/// windows the score is 100 if there are no alarms, and 0 otherwise.
+pub fn nab_score<S1: Data<Elem = bool>, S2: Data<Elem = bool>>(
+    y_true: &ArrayBase<S1, Ix1>,
+    y_alarm: &ArrayBase<S2, Ix1>,
+    profile: NabProfile,
+) -> f64 {
+    let (w_tp, w_fp, w_fn) = profile.weights();
+    let wins = ranges(y_true);
+    let y_alarm = align(y_alarm, y_true.len());
+    if wins.is_empty() {
+        return if y_alarm.iter().any(|&a| a) { 0. } else { 100. };
+    }
+
+    let mut raw = 0.;
+    let mut detected = vec![false; wins.len()];
+    let mut prev_win: Option<&Range<usize>> = None;
+    let mut wins_iter = wins.iter().enumerate().peekable();
+    for (i, &alarm) in y_alarm.iter().enumerate() {
+        while let Some(&(_, win)) = wins_iter.peek() {
+            if win.start > i {
+                break;
+            }
+            if win.contains(&i) {
+                break;
+            }
+            prev_win = Some(win);
+            wins_iter.next();
+        }
+        if !alarm {
+            continue;
+        }
+        match wins_iter.peek() {
+            Some(&(j, win)) if win.contains(&i) => {
+                if !detected[j] {
+                    detected[j] = true;
+                    let pos = -((win.end - i) as f64) / (win.len() as f64);
+                    raw += w_tp * scaled_sigmoid(pos);
+                }
+            }
+            _ => {
+                let sigmoid = match prev_win {
+                    None => -1.,
+                    Some(win) => {
+                        let past = (i + 1 - win.end) as f64 / (win.len().max(2) - 1) as f64;
+                        scaled_sigmoid(past)
+                    }
+                };
+                raw += w_fp * sigmoid;
+            }
+        }
+    }
+    let n_missed = detected.iter().filter(|&&d| !d).count();
+    raw -= w_fn * (n_missed as f64);
+
+    let null = -w_fn * (wins.len() as f64);
+    let perfect = w_tp * scaled_sigmoid(-1.) * (wins.len() as f64);
+    100. * (raw - null) / (perfect - null)
+}
+
 pub fn exp_bst_path_length(n: usize) -> f32 {
     let h = |i: f32| i.ln() + 0.577_215_7;
     let c = |n: f32| 2.0 * h(n - 1.0) - (2.0 * (n - 1.0) / n);
@@ -0,0 +1,100 @@
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use rustfft::{num_complex::Complex, FftPlanner};
+
+const N_PERMUTATIONS: usize = 100;
+const CONFIDENCE: f32 = 0.99;
+
+fn power_spectrum(planner: &mut FftPlanner<f32>, values: &[f32]) -> Vec<f32> {
+    let fft = planner.plan_fft_forward(values.len());
+    let mut buf: Vec<_> = values.iter().map(|&v| Complex::new(v, 0.)).collect();
+    fft.process(&mut buf);
+    buf.iter().map(|c| c.norm_sqr()).collect()
+}
+
+fn acf(planner: &mut FftPlanner<f32>, values: &[f32]) -> Vec<f32> {
+    let n = values.len();
+    let mut padded = values.to_vec();
+    padded.resize(2 * n, 0.);
+    let power = power_spectrum(planner, &padded);
+    let ifft = planner.plan_fft_inverse(2 * n);
+    let mut buf: Vec<_> = power.into_iter().map(|p| Complex::new(p, 0.)).collect();
+    ifft.process(&mut buf);
+    // unbiased estimate, so that the ACF does not decay with the lag
+    let c0 = buf[0].re / (n as f32);
+    buf[..n]
+        .iter()
+        .enumerate()
+        .map(|(lag, c)| {
+            if c0 == 0. {
+                0.
+            } else {
+                c.re / ((n - lag) as f32) / c0
+            }
+        })
+        .collect()
+}
+
+// Power above which a periodogram peak is unlikely to come from a series without periodicity,
+// estimated from the maximal power of random permutations of the series.
+fn power_threshold(planner: &mut FftPlanner<f32>, values: &[f32]) -> f32 {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut shuffled = values.to_vec();
+    let mut max_powers: Vec<f32> = (0..N_PERMUTATIONS)
+        .map(|_| {
+            shuffled.shuffle(&mut rng);
+            let power = power_spectrum(planner, &shuffled);
+            power[1..=values.len() / 2]
+                .iter()
+                .copied()
+                .fold(0., f32::max)
+        })
+        .collect();
+    max_powers.sort_unstable_by(f32::total_cmp);
+    max_powers[((N_PERMUTATIONS as f32 * CONFIDENCE) as usize).min(N_PERMUTATIONS - 1)]
+}
+
+// A candidate period is kept if it sits on a hill of the ACF: climbing the ACF from the
+// candidate must reach a positive local peak without leaving the range of periods of the
+// periodogram bin, otherwise the candidate is on a slope. It is then refined to that peak.
+fn validate(acf: &[f32], n: usize, k: usize) -> Option<usize> {
+    let lb = (n / (k + 1)).max(2);
+    let ub = (n / (k - 1)).min(acf.len() - 2);
+    if lb >= ub {
+        return None;
+    }
+    let mut p = ((n as f32 / k as f32).round() as usize).clamp(lb, ub);
+    loop {
+        let next = if acf[p + 1] > acf[p] {
+            p + 1
+        } else if acf[p - 1] > acf[p] {
+            p - 1
+        } else {
+            break;
+        };
+        if !(lb..=ub).contains(&next) {
+            return None;
+        }
+        p = next;
+    }
+    (acf[p] > 0.).then_some(p)
+}
+
+// Dominant period of the series following Autoperiod (Vlachos et al.): periodogram peaks above
+// a permutation threshold are proposed, then validated and refined on the autocorrelation.
+pub fn detect_period(values: &[f32]) -> Option<usize> {
+    let n = values.len();
+    if n < 8 {
+        return None;
+    }
+    let mean = values.iter().sum::<f32>() / (n as f32);
+    let centered: Vec<_> = values.iter().map(|v| v - mean).collect();
+
+    let mut planner = FftPlanner::new();
+    let power = power_spectrum(&mut planner, &centered);
+    let threshold = power_threshold(&mut planner, &centered);
+    let acf = acf(&mut planner, &centered);
+
+    let mut candidates: Vec<_> = (2..n / 2).filter(|&k| power[k] > threshold).collect();
+    candidates.sort_unstable_by(|&k1, &k2| power[k2].total_cmp(&power[k1]));
+    candidates.into_iter().find_map(|k| validate(&acf, n, k))
+}
@@ -1,3 +1,9 @@
 pub use crate::adapter::prelude::*;
 pub use crate::algorithm::prelude::*;
-pub use crate::metric::{k_largest, k_smallest, pr, pr_n1, prauc, rocauc};
+pub use crate::metric::{
+    f1_at, k_largest, k_smallest, nab_score, pr, pr_at_k, pr_n1, prauc, range_pr, range_rc, rc,
+    rocauc, top_k, Bias, Cardinality, NabProfile,
+};
+pub use crate::period::detect_period;
+pub use crate::score::{AnomalyScore, PathLength, Score};
+pub use crate::tune::{tune, TuneGrid};
@@ -0,0 +1,99 @@
+use std::{cmp::Ordering, fmt, marker::PhantomData};
+
+pub trait Orientation: Copy {
+    const HIGHER_IS_ANOMALOUS: bool;
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Lower;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Higher;
+
+impl Orientation for Lower {
+    const HIGHER_IS_ANOMALOUS: bool = false;
+}
+
+impl Orientation for Higher {
+    const HIGHER_IS_ANOMALOUS: bool = true;
+}
+
+// Scores are ordered by how anomalous they are, so the greatest score is always the most
+// anomalous one, whatever the orientation of the underlying value.
+#[repr(transparent)]
+pub struct Score<O> {
+    value: f32,
+    orientation: PhantomData<O>,
+}
+
+pub type PathLength = Score<Lower>;
+pub type AnomalyScore = Score<Higher>;
+
+impl<O: Orientation> Score<O> {
+    pub fn new(value: f32) -> Self {
+        Self {
+            value,
+            orientation: PhantomData,
+        }
+    }
+
+    pub fn from_anomaly(anomaly: f32) -> Self {
+        if O::HIGHER_IS_ANOMALOUS {
+            Self::new(anomaly)
+        } else {
+            Self::new(-anomaly)
+        }
+    }
+
+    pub fn value(self) -> f32 {
+        self.value
+    }
+
+    pub fn anomaly(self) -> f32 {
+        if O::HIGHER_IS_ANOMALOUS {
+            self.value
+        } else {
+            -self.value
+        }
+    }
+}
+
+impl<O> Clone for Score<O> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<O> Copy for Score<O> {}
+
+impl<O> fmt::Debug for Score<O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+impl<O: Orientation> PartialEq for Score<O> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<O: Orientation> Eq for Score<O> {}
+
+impl<O: Orientation> PartialOrd for Score<O> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// NaN scores are equal to each other and above every other score, whatever the orientation, so
+// they show up among the top anomalies instead of hiding. Zeros compare equal whatever their sign.
+impl<O: Orientation> Ord for Score<O> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (a, b) = (self.anomaly(), other.anomaly());
+        match (a.is_nan(), b.is_nan()) {
+            (false, false) => a.partial_cmp(&b).unwrap(),
+            (a_nan, b_nan) => a_nan.cmp(&b_nan),
+        }
+    }
+}
@@ -14,7 +14,7 @@ use super::{
 pub fn eif_split_const<const E: bool, const D: usize>(
     x: &Array2<f32>,
     cfg: &Config,
-) -> Array1<f32> {
+) -> Array1<AnomalyScore> {
     assert_eq!(D, x.dim().1, "wrong dataset dimensionality");
     let train = x.slice(s!(0..cfg.n_points, ..));
     let test = x.slice(s!(cfg.n_points.., ..));
@@ -26,7 +26,7 @@ pub fn eif_split_const<const E: bool, const D: usize>(
     };
     let f = Forest::from_slice(const_copy::<D>(train.view()).as_slice(), &options).unwrap();
     const_copied(test.outer_iter())
-        .map(|p| f.score(&p) as f32)
+        .map(|p| AnomalyScore::new(f.score(&p) as f32))
         .collect()
 }
 
@@ -81,8 +81,8 @@ pub fn bench_rrcf_split(x: &Array2<f32>, y_true: &Array1<bool>, cfg: &Config) ->
             points.by_ref().take(cfg.n_points).for_each(|p| {
                 f.update(p.to_owned().as_slice().unwrap(), 0);
             });
-            let y_pred: Array1<f32> = points
-                .map(|p| f.score(p.as_slice().unwrap()) as f32)
+            let y_pred: Array1<AnomalyScore> = points
+                .map(|p| AnomalyScore::new(f.score(p.as_slice().unwrap()) as f32))
                 .collect();
             arr1(&[
                 rocauc(y_true, &y_pred),
@@ -5,6 +5,7 @@ use crate::prelude::ConfigBuilder;
 mod bench;
 mod cmp_algorithms;
 mod cmp_window_sizes;
+mod tune_configs;
 mod utils;
 
 lazy_static! {
@@ -0,0 +1,111 @@
+use std::{error::Error, fmt::Write, path::PathBuf};
+
+use ndarray::Array2;
+
+use crate::{
+    algorithm::bounding_box::BoundingBox,
+    prelude::*,
+    tests::utils::{read_npz, run_globs, save_txt},
+    tune::{best_trial, Trial, TuneReport},
+};
+
+use super::{BASE_CB, ROOT};
+
+fn run<const L: bool>(name: &str, path: PathBuf) -> Result<String, Box<dyn Error>> {
+    let mut out = String::new();
+    writeln!(out, "--- {name} ---")?;
+
+    let (mut x, y_true) = read_npz(&path);
+    let bb = x.outer_iter().bb().ok_or("no bounding box")?;
+    x.outer_iter_mut().normalise(&bb);
+    let y_true = if L { Some(&y_true) } else { None };
+    let (cfg, report) = tune(&x, y_true, &BASE_CB, &TuneGrid::default());
+    write!(out, "{report}")?;
+
+    let best = report.best();
+    assert_eq!(
+        (cfg.n_points, cfg.n_trees, cfg.granularity, cfg.window),
+        (best.n_points, best.n_trees, best.granularity, best.window)
+    );
+    let top = report
+        .trials
+        .iter()
+        .max_by(|t1, t2| t1.mean.total_cmp(&t2.mean))
+        .unwrap();
+    assert!(best.mean >= top.mean - top.std.max(1e-3));
+
+    Ok(out)
+}
+
+#[test]
+fn tune_configs_labelled() {
+    let out = run_globs(run::<true>, &["in/toy/*.npz"]).concat();
+    save_txt(ROOT, "tune_configs_labelled", &out);
+}
+
+#[test]
+fn tune_configs_unlabelled() {
+    let out = run_globs(run::<false>, &["in/toy/*.npz"]).concat();
+    save_txt(ROOT, "tune_configs_unlabelled", &out);
+}
+
+fn trial(n_points: usize, mean: f64, std: f64) -> Trial {
+    Trial {
+        n_points,
+        n_trees: 32,
+        granularity: 1,
+        window: 1024,
+        shingle: 1,
+        mean,
+        std,
+        secs: 0.,
+    }
+}
+
+#[test]
+fn best_trial_planted() {
+    let trials = [
+        trial(128, 0.5, 0.01),
+        trial(512, 0.9, 0.01),
+        trial(256, 0.6, 0.01),
+    ];
+    assert_eq!(best_trial(&trials), 1);
+}
+
+#[test]
+fn best_trial_ties_to_cheaper() {
+    let trials = [
+        trial(512, 0.8, 0.),
+        trial(128, 0.8, 0.),
+        trial(256, 0.8, 0.),
+    ];
+    assert_eq!(best_trial(&trials), 1);
+    // within one standard deviation of the best counts as a tie
+    let trials = [
+        trial(512, 0.8, 0.1),
+        trial(128, 0.75, 0.1),
+        trial(64, 0.5, 0.1),
+    ];
+    assert_eq!(best_trial(&trials), 1);
+}
+
+#[test]
+fn seeded_tune_is_reproducible() {
+    let x = Array2::from_shape_fn((400, 1), |(i, _)| ((i * 37) % 100) as f32 / 100.);
+    let cb = ConfigBuilder::default()
+        .bounding_box(BoundingBox::unit(1))
+        .seed(7);
+    let grid = TuneGrid {
+        n_points: vec![16, 32],
+        n_trees: vec![4],
+        granularity: vec![1],
+        window: vec![64],
+        shingle: vec![1],
+        n_repetitions: 2,
+    };
+    let (_, r1) = tune(&x, None, &cb, &grid);
+    let (_, r2) = tune(&x, None, &cb, &grid);
+    let means = |r: &TuneReport| r.trials.iter().map(|t| t.mean).collect::<Vec<_>>();
+    assert_eq!(means(&r1), means(&r2));
+    assert_eq!(r1.best, r2.best);
+}
@@ -22,9 +22,9 @@ pub fn bench_offline(x: &Array2<f32>, y_true: &Array1<bool>, cb: &ConfigBuilder)
                     tree.batch_insert(&sample);
                 }
                 let scores = f.batch_score(x);
-                k_smallest(&scores, n1)
+                top_k(&scores, n1)
             });
-            arr1(&[cfg.n_points as f64, pr(y_true, &anomalies), dt])
+            arr1(&[cfg.n_points as f64, pr(y_true, &anomalies), dt, 0.])
         })
         .collect();
     BenchRes::new(res)
@@ -40,8 +40,14 @@ pub fn bench_one_way_coordinator(
         .into_iter()
         .map(|_| {
             let seeded_cfg = cb.clone().seed(thread_rng().gen()).build();
-            let ((f, anomalies), dt) = time(|| x.outer_iter().one_way_coordinator(&seeded_cfg, n1));
-            arr1(&[f.n_points() as f64, pr(y_true, &anomalies), dt])
+            let ((f, anomalies, cost), dt) =
+                time(|| x.outer_iter().one_way_coordinator(&seeded_cfg, n1));
+            arr1(&[
+                f.n_points() as f64,
+                pr(y_true, &anomalies),
+                dt,
+                cost.bytes() as f64,
+            ])
         })
         .collect();
     BenchRes::new(res)
@@ -57,8 +63,14 @@ pub fn bench_two_way_par_streams(
         .into_iter()
         .map(|_| {
             let seeded_cfg = cb.clone().seed(thread_rng().gen()).build();
-            let ((f, anomalies), dt) = time(|| x.outer_iter().two_way_par_streams(&seeded_cfg, n1));
-            arr1(&[f.n_points() as f64, pr(y_true, &anomalies), dt])
+            let ((f, anomalies, cost), dt) =
+                time(|| x.outer_iter().two_way_par_streams(&seeded_cfg, n1));
+            arr1(&[
+                f.n_points() as f64,
+                pr(y_true, &anomalies),
+                dt,
+                cost.bytes() as f64,
+            ])
         })
         .collect();
     BenchRes::new(res)
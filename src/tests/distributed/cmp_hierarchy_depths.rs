@@ -0,0 +1,70 @@
+use std::{error::Error, fmt::Write, path::PathBuf};
+
+use ndarray::prelude::*;
+use rand::{thread_rng, Rng};
+
+use super::{BASE_CB, N_REPETITIONS, SKETCH_SIZES};
+use crate::{
+    prelude::*,
+    tests::utils::{read_npz, run_globs, save_txt, BenchRes},
+};
+
+const N_MACHINES: usize = 128;
+const FAN_INS: [usize; 5] = [128, 16, 8, 4, 2];
+
+// Precision, total traffic and traffic into the coordinator, in kB.
+fn bench(
+    x: &Array2<f32>,
+    y_true: &Array1<bool>,
+    cb: &ConfigBuilder,
+    h: &HierarchyConfig,
+) -> BenchRes {
+    let n1 = y_true.iter().filter(|&&a| a).count();
+    let res = (0..N_REPETITIONS)
+        .map(|_| {
+            let cfg = cb.clone().seed(thread_rng().gen()).build();
+            let (_f, anomalies, cost) = x.outer_iter().hierarchical_coordinator(&cfg, n1, h);
+            let last = cost.n_rounds() - 1;
+            let root_bytes: usize = cost.round(last).map(|c| c.bytes_sent).sum();
+            arr1(&[
+                pr(y_true, &anomalies),
+                cost.bytes() as f64 / 1e3,
+                root_bytes as f64 / 1e3,
+            ])
+        })
+        .collect();
+    BenchRes::new(res)
+}
+
+fn run(name: &str, path: PathBuf) -> Result<String, Box<dyn Error>> {
+    let mut out = String::new();
+    writeln!(out, "--- {name} ---")?;
+    writeln!(out, "fan-in\tdepth\ts\tpr\tkB\tcoordinator kB")?;
+
+    let (x, y_true) = read_npz(path);
+    let bb = x.outer_iter().bb().ok_or("no bounding box")?;
+    let cb = BASE_CB
+        .clone()
+        .bounding_box(bb)
+        .n_points(2048)
+        .n_machines(N_MACHINES);
+
+    for fan_in in FAN_INS {
+        for s in SKETCH_SIZES {
+            let h = HierarchyConfig::new(fan_in, s);
+            let res = bench(&x, &y_true, &cb.clone().sketch_size(s), &h);
+            write!(out, "{fan_in}\t{}\t{s}\t", h.depth(N_MACHINES))?;
+            write!(out, "{:.2} ({:.2})\t", res.means[0], res.stds[0])?;
+            write!(out, "{:.1} ({:.1})\t", res.means[1], res.stds[1])?;
+            writeln!(out, "{:.1} ({:.1})", res.means[2], res.stds[2])?;
+        }
+    }
+
+    Ok(out)
+}
+
+#[test]
+fn cmp_hierarchy_depths() {
+    let out = run_globs(run, &["in/toy/*.npz", "in/real/*.npz"]).concat();
+    save_txt("out/hierarchical_coordinator", "cmp_hierarchy_depths", &out);
+}
@@ -15,7 +15,7 @@ where
 {
     let mut out = String::new();
     writeln!(out, "--- {name} ---")?;
-    writeln!(out, "m\ts\tsize\tpr")?;
+    writeln!(out, "m\ts\tsize\tpr\tkB")?;
 
     let (x, y_true) = read_npz(path);
     let bb = x.outer_iter().bb().ok_or("no bounding box")?;
@@ -27,7 +27,8 @@ where
             let res = bencher(&x, &y_true, &cb);
             write!(out, "{}\t{}\t", m, s)?;
             write!(out, "{:.2} ({:.2})\t", res.means[0], res.stds[0])?;
-            writeln!(out, "{:.2} ({:.2})", res.means[1], res.stds[1])?;
+            write!(out, "{:.2} ({:.2})\t", res.means[1], res.stds[1])?;
+            writeln!(out, "{:.1} ({:.1})", res.means[3] / 1e3, res.stds[3] / 1e3)?;
         }
     }
 
@@ -0,0 +1,77 @@
+use std::{error::Error, fmt::Write, path::PathBuf};
+
+use itertools::Itertools;
+use ndarray::prelude::*;
+use rand::{thread_rng, Rng};
+
+use super::{BASE_CB, N_REPETITIONS};
+use crate::{
+    prelude::*,
+    tests::utils::{read_npz, run_globs, save_txt, BenchRes},
+};
+
+// The partitioner is built from the config of every repetition, so that the random ones are
+// seeded with it.
+fn bench<P: Partitioner>(
+    x: &Array2<f32>,
+    y_true: &Array1<bool>,
+    cb: &ConfigBuilder,
+    partitioner: impl Fn(&Config) -> P,
+) -> BenchRes {
+    let n1 = y_true.iter().filter(|&&a| a).count();
+    let res = (0..N_REPETITIONS)
+        .map(|_| {
+            let cfg = cb.clone().seed(thread_rng().gen()).build();
+            let partitioner = partitioner(&cfg);
+            let (_f, one_way, _cost) =
+                x.outer_iter()
+                    .one_way_coordinator_with(&cfg, n1, &partitioner);
+            let (_f, two_way, _cost) =
+                x.outer_iter()
+                    .two_way_par_streams_with(&cfg, n1, &partitioner);
+            arr1(&[pr(y_true, &one_way), pr(y_true, &two_way)])
+        })
+        .collect();
+    BenchRes::new(res)
+}
+
+fn run(name: &str, path: PathBuf) -> Result<String, Box<dyn Error>> {
+    let mut out = String::new();
+    writeln!(out, "--- {name} ---")?;
+    writeln!(out, "partitioner\tone-way pr\ttwo-way pr")?;
+
+    let (x, y_true) = read_npz(path);
+    let bb = x.outer_iter().bb().ok_or("no bounding box")?;
+    let cb = BASE_CB.clone().bounding_box(bb).n_machines(8);
+    let anomalies: Vec<_> = y_true.iter().positions(|&a| a).collect();
+
+    let results = [
+        (
+            "random ranges",
+            bench(&x, &y_true, &cb, RandomRanges::from_config),
+        ),
+        (
+            "random machines",
+            bench(&x, &y_true, &cb, RandomMachines::from_config),
+        ),
+        ("round-robin", bench(&x, &y_true, &cb, |_| RoundRobin)),
+        ("time range", bench(&x, &y_true, &cb, |_| TimeRange)),
+        (
+            "skewed",
+            bench(&x, &y_true, &cb, |_| Skewed::new(anomalies.clone())),
+        ),
+    ];
+    for (partitioner, res) in results {
+        write!(out, "{partitioner}\t")?;
+        write!(out, "{:.2} ({:.2})\t", res.means[0], res.stds[0])?;
+        writeln!(out, "{:.2} ({:.2})", res.means[1], res.stds[1])?;
+    }
+
+    Ok(out)
+}
+
+#[test]
+fn cmp_partitioners() {
+    let out = run_globs(run, &["in/toy/*.npz", "in/real/*.npz"]).concat();
+    save_txt("out/distributed", "cmp_partitioners", &out);
+}
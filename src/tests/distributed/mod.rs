@@ -2,8 +2,11 @@ use crate::prelude::ConfigBuilder;
 use lazy_static::lazy_static;
 
 mod bench;
+mod cmp_hierarchy_depths;
 mod cmp_machine_sketch_sizes;
+mod cmp_partitioners;
 mod cmp_sample_sketch_sizes;
+mod remote;
 
 lazy_static! {
     static ref BASE_CB: ConfigBuilder = {
@@ -0,0 +1,68 @@
+use std::{error::Error, fmt::Write, net::TcpListener, path::PathBuf, thread};
+
+use ndarray::prelude::*;
+
+use super::{BASE_CB, MACHINE_SIZES};
+use crate::{
+    adapter::remote::one_way_coordinator,
+    prelude::*,
+    tests::utils::{read_npz, run_globs, save_txt},
+};
+
+// One worker thread per machine, each over a contiguous chunk of the data, talking to the
+// coordinator over localhost.
+fn run_remote(x: &Array2<f32>, cfg: &Config, n1: usize) -> Result<Vec<usize>, Box<dyn Error>> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let addr = listener.local_addr()?;
+    let chunk_size = x.nrows().div_ceil(cfg.n_machines);
+    let (_f, anomalies, _cost) = thread::scope(|scope| {
+        for (m, chunk) in x.axis_chunks_iter(Axis(0), chunk_size).enumerate() {
+            scope.spawn(move || {
+                let offset = m * chunk_size;
+                chunk
+                    .outer_iter()
+                    .enumerate()
+                    .map(|(i, p)| (offset + i, p))
+                    .one_way_worker(cfg, n1, m, addr)
+                    .expect("worker failed");
+            });
+        }
+        one_way_coordinator(&listener, cfg, n1)
+    })?;
+    Ok(anomalies)
+}
+
+fn run(name: &str, path: PathBuf) -> Result<String, Box<dyn Error>> {
+    let mut out = String::new();
+    writeln!(out, "--- {name} ---")?;
+    writeln!(out, "m\tlocal pr\tremote pr")?;
+
+    let (x, y_true) = read_npz(path);
+    let n1 = y_true.iter().filter(|&&a| a).count();
+    let bb = x.outer_iter().bb().ok_or("no bounding box")?;
+
+    for m in MACHINE_SIZES {
+        let cfg = BASE_CB
+            .clone()
+            .bounding_box(bb.clone())
+            .n_machines(m)
+            .seed(0)
+            .build();
+        let (_f, local, _cost) = x.outer_iter().one_way_coordinator(&cfg, n1);
+        let remote = run_remote(&x, &cfg, n1)?;
+        writeln!(
+            out,
+            "{m}\t{:.2}\t{:.2}",
+            pr(&y_true, &local),
+            pr(&y_true, &remote)
+        )?;
+    }
+
+    Ok(out)
+}
+
+#[test]
+fn remote() {
+    let out = run_globs(run, &["in/toy/*.npz"]).concat();
+    save_txt("out/remote", "one_way_coordinator", &out);
+}
@@ -29,6 +29,7 @@ fn run(name: &str, path: PathBuf) -> Result<(), Box<dyn Error>> {
             .iter()
             .map(|&(x, y)| f.score(&arr1(&[x, y])))
             .transform(&cfg)
+            .map(AnomalyScore::value)
             .collect();
 
         let mut plot = Plot::new();
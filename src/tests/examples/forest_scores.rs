@@ -21,6 +21,7 @@ fn plot_tree(name: &str, path: PathBuf) -> Result<(), Box<dyn Error>> {
             .outer_iter()
             .rsf_split(&cfg)
             .transform(&cfg)
+            .map(AnomalyScore::value)
             .map(f64::from)
             .collect();
 
@@ -78,6 +78,7 @@ fn split_sampling() {
         .shingle(cfg.shingle)
         .rsf_split(&cfg)
         .transform(&cfg)
+        .map(AnomalyScore::value)
         .collect();
     let mut plot = Plot::new();
     plot.add_trace(Scatter::new(0..N, x.into_iter()).show_legend(false));
@@ -99,12 +100,14 @@ fn joint_vs_split_reservoir_sampling() {
         .shingle(cfg.shingle)
         .rsf_reservoir::<false>(&cfg)
         .transform(&cfg)
+        .map(AnomalyScore::value)
         .collect();
     let scores_multiple: Array1<_> = x
         .outer_iter()
         .shingle(cfg.shingle)
         .rsf_reservoir::<true>(&cfg)
         .transform(&cfg)
+        .map(AnomalyScore::value)
         .collect();
     let mut plot = Plot::new();
     plot.add_trace(
@@ -137,12 +140,14 @@ fn joint_vs_split_window_sampling() {
         .shingle(cfg.shingle)
         .rsf_window::<false>(&cfg)
         .transform(&cfg)
+        .map(AnomalyScore::value)
         .collect();
     let scores_multiple: Array1<_> = x
         .outer_iter()
         .shingle(cfg.shingle)
         .rsf_window::<true>(&cfg)
         .transform(&cfg)
+        .map(AnomalyScore::value)
         .collect();
     let mut plot = Plot::new();
     plot.add_trace(
@@ -182,6 +187,7 @@ fn varying_shingle_size() {
             .shingle(s)
             .rsf_window::<true>(&cfg)
             .transform(&cfg)
+            .map(AnomalyScore::value)
             .collect();
         plot.add_trace(
             Scatter::new((N - scores.len())..N, scores)
@@ -42,7 +42,7 @@ fn run(name: &str, path: PathBuf) -> Result<(), Box<dyn Error>> {
 
         let n1 = y_true.iter().filter(|&&a| a).count();
         let scores = f[1].batch_score(&x);
-        let anomalies = k_smallest(&scores, n1);
+        let anomalies = top_k(&scores, n1);
 
         let bounds = BoundingBox::with_double_range(&cfg.bb).bounds;
         let mut layout = Layout::new()
@@ -66,9 +66,9 @@ pub fn bench_rrcf_split(
                 1.0,
             );
             trn.iter().for_each(|p| f.update(p.as_slice().unwrap(), 0));
-            let y_pred: Array1<f32> = tst
+            let y_pred: Array1<AnomalyScore> = tst
                 .iter()
-                .map(|p| f.score(p.as_slice().unwrap()) as f32)
+                .map(|p| AnomalyScore::new(f.score(p.as_slice().unwrap()) as f32))
                 .collect();
             arr1(&[
                 rocauc(y_true, &y_pred),
@@ -93,7 +93,7 @@ pub fn bench_rsf_split(
             let sketches = sketch_graphs(graphs, sl_cfg, mode);
             let bb = sketches.iter().map(|sketch| sketch.view()).bb().unwrap();
             let rsf_cfg = rsf_cb.clone().bounding_box(bb).build();
-            let y_pred: Array1<f32> = sketches
+            let y_pred: Array1<AnomalyScore> = sketches
                 .into_iter()
                 .rsf_split(&rsf_cfg)
                 .transform(&rsf_cfg)
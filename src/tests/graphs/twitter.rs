@@ -104,6 +104,7 @@ fn twitter_world_cup() {
         .into_iter()
         .rsf_window::<true>(&rsf_cfg)
         .transform(&rsf_cfg)
+        .map(AnomalyScore::value)
         .collect();
     let shift = n - y_pred.len();
 
@@ -215,6 +216,7 @@ fn twitter_security() {
         .into_iter()
         .rsf_window::<true>(&rsf_cfg)
         .transform(&rsf_cfg)
+        .map(AnomalyScore::value)
         .collect();
     let shift = n - y_pred.len();
 
@@ -6,4 +6,5 @@ mod paper;
 mod props;
 mod scalability;
 mod time_series;
+mod unit;
 pub mod utils;
@@ -161,13 +161,14 @@ fn run() -> Result<(), Box<dyn Error>> {
             .iter()
             .map(|&(x, y)| f.score(&arr1(&[x, y])))
             .transform(&cfg)
+            .map(AnomalyScore::value)
             .collect();
         let scores2: Array1<_> = x
             .outer_iter()
             .map(|p| f.score(&p))
             .transform(&cfg)
             .collect();
-        let anomalies2 = k_largest(&scores2, n1);
+        let anomalies2 = top_k(&scores2, n1);
         let n_true2 = anomalies2.iter().filter(|&&i| y_true[i]).count();
 
         // - heatmap
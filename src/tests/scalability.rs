@@ -32,7 +32,8 @@ pub fn bench_pre_filter_unpar(
             //     let ((f, anomalies), dt) = time(|| x.outer_iter().pre_filter(&seeded_cfg, n1));
             //     arr1(&[f.n_points() as f64, pr(y_true, &anomalies), dt])
             // })
-            let ((f, anomalies), dt) = time(|| x.outer_iter().one_way_coordinator(&seeded_cfg, n1));
+            let ((f, anomalies, _cost), dt) =
+                time(|| x.outer_iter().one_way_coordinator(&seeded_cfg, n1));
             arr1(&[f.n_points() as f64, pr(y_true, &anomalies), dt])
         })
         .collect::<Vec<_>>();
@@ -15,11 +15,7 @@ use crate::{
     tests::utils::{save_jpeg, save_txt},
 };
 
-use super::{
-    input::input,
-    utils::{autoperiod, WINDOWS},
-    ROOT,
-};
+use super::{input::input, utils::WINDOWS, ROOT};
 
 pub fn run(
     ts: &[String],
@@ -27,15 +23,21 @@ pub fn run(
     wins: &[[String; 2]],
     cfg: &Config,
     mut layout: Layout,
-) -> Plot {
-    let scores: Array1<f32> = x
+) -> (Plot, Vec<Option<AnomalyScore>>) {
+    let scores: Vec<Option<AnomalyScore>> = x
         .outer_iter()
-        .shingle(cfg.shingle)
-        .rsf_window::<true>(cfg)
-        .transform(cfg)
+        .aligned(|points| {
+            points
+                .shingle(cfg.shingle)
+                .rsf_window::<true>(cfg)
+                .transform(cfg)
+        })
         .collect();
-    let n = x.dim().0;
-    let shift = n - scores.len();
+    let (ts_out, y_out): (Vec<_>, Vec<_>) = ts
+        .iter()
+        .zip(&scores)
+        .filter_map(|(t, s)| s.map(|s| (t.clone(), s.value())))
+        .unzip();
     let mut plot = Plot::new();
     plot.add_trace(
         Scatter::new(ts.to_vec(), Vec::from_iter(x))
@@ -43,7 +45,7 @@ pub fn run(
             .name("input"),
     );
     plot.add_trace(
-        Scatter::new(ts[shift..n].to_vec(), scores)
+        Scatter::new(ts_out, y_out)
             .y_axis("y2")
             .show_legend(false)
             .name("output"),
@@ -75,7 +77,18 @@ pub fn run(
                     .title(Title::new("anomaly score")),
             ),
     );
-    plot
+    (plot, scores)
+}
+
+fn labels(ts: &[String], wins: &[[String; 2]]) -> Array1<bool> {
+    let wins: Vec<_> = wins
+        .iter()
+        .map(|win| (parse_date(&win[0]), parse_date(&win[1])))
+        .collect();
+    ts.iter()
+        .map(|t| parse_date(t))
+        .map(|t| wins.iter().any(|&(lb, ub)| lb <= t && t <= ub))
+        .collect()
 }
 
 fn parse_date(date: &str) -> i64 {
@@ -87,7 +100,11 @@ fn parse_date(date: &str) -> i64 {
 #[test]
 fn run_nab_labelled() {
     let mut out = String::new();
-    writeln!(out, "class\tname\t#points\t#anomalies\tperiod").unwrap();
+    writeln!(
+        out,
+        "class\tname\t#points\t#anomalies\tperiod\trocauc\tnab\tnab-low-fp\tnab-low-fn"
+    )
+    .unwrap();
     let (tx, rx) = channel::<String>();
 
     let entries: Vec<_> = WINDOWS.iter().collect();
@@ -102,11 +119,13 @@ fn run_nab_labelled() {
 
             let (ts, x) = input(class, name);
             let values: Vec<_> = x.iter().cloned().take(1024).collect();
-            let period = autoperiod(values).unwrap();
-            if period == 1 {
-                println!("{class} {name} - no period");
-                return;
-            }
+            let period = match detect_period(&values) {
+                Some(period) => period,
+                None => {
+                    println!("{class} {name} - no period");
+                    return;
+                }
+            };
             // let shingle = usize::max(128, period);
             let shingle = period;
 
@@ -133,13 +152,26 @@ fn run_nab_labelled() {
                 .window(window)
                 .shingle(shingle)
                 .build();
-            let plot = run(&ts, x, wins.as_slice(), &cfg, Layout::new());
+            let (plot, scores) = run(&ts, x, wins.as_slice(), &cfg, Layout::new());
             save_jpeg(&format!("{ROOT}/{class}"), name, plot, 900, 450);
+
+            let (y_true, scores): (Vec<_>, Vec<_>) = labels(&ts, wins)
+                .into_iter()
+                .zip(scores)
+                .filter_map(|(y, s)| s.map(|s| (y, s)))
+                .unzip();
+            let (y_true, scores) = (Array1::from(y_true), Array1::from(scores));
+            let spot_cfg = SpotConfig::new(1e-3, 512);
+            let y_alarm: Array1<bool> = scores.iter().copied().spot(&spot_cfg).collect();
             tx.send(format!(
-                "{class}\t{name}\t{}\t{}\t{}\n",
+                "{class}\t{name}\t{}\t{}\t{}\t{:.2}\t{:.2}\t{:.2}\t{:.2}\n",
                 ts.len(),
                 wins.len(),
-                period
+                period,
+                rocauc(&y_true, &scores),
+                nab_score(&y_true, &y_alarm, NabProfile::Standard),
+                nab_score(&y_true, &y_alarm, NabProfile::LowFp),
+                nab_score(&y_true, &y_alarm, NabProfile::LowFn),
             ))
             .unwrap();
         });
@@ -1,7 +1,6 @@
 use std::{collections::HashMap, fs::read_to_string};
 
 use lazy_static::lazy_static;
-use pyo3::{types::PyModule, PyResult, Python};
 use serde::Deserialize;
 use serde_json::Value;
 
@@ -24,14 +23,3 @@ lazy_static! {
         hm
     };
 }
-
-pub fn autoperiod(values: Vec<f32>) -> PyResult<usize> {
-    let py_main = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/main.py"));
-    Python::with_gil(|py| {
-        let main = PyModule::from_code(py, py_main, "main.py", "main")?;
-        let p = main.getattr("p")?;
-        let res = p.call1((values,))?;
-        let period = res.extract::<usize>()?;
-        Ok(period)
-    })
-}
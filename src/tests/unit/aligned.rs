@@ -0,0 +1,76 @@
+use ndarray::prelude::*;
+
+use crate::{algorithm::bounding_box::BoundingBox, prelude::*};
+
+fn points(n: usize) -> Array2<f32> {
+    Array2::from_shape_fn((n, 1), |(i, _)| ((i * 37) % 100) as f32 / 100.)
+}
+
+fn n_warm_up<T>(out: &[Option<T>]) -> usize {
+    let n = out.iter().take_while(|o| o.is_none()).count();
+    assert!(out[n..].iter().all(Option::is_some));
+    n
+}
+
+#[test]
+fn shingle_warm_up() {
+    let x = points(10);
+    let out: Vec<_> = x.outer_iter().aligned(|it| it.shingle(3)).collect();
+    assert_eq!(out.len(), 10);
+    // the shingle of the first `s` items is not emitted
+    assert_eq!(n_warm_up(&out), 3);
+    assert_eq!(
+        out[3].as_ref().unwrap(),
+        &array![x[[1, 0]], x[[2, 0]], x[[3, 0]]]
+    );
+}
+
+#[test]
+fn window_warm_up() {
+    let cfg = ConfigBuilder::default()
+        .bounding_box(BoundingBox::unit(3))
+        .n_points(16)
+        .n_trees(8)
+        .window(32)
+        .seed(0)
+        .build();
+    let x = points(100);
+    let out: Vec<_> = x
+        .outer_iter()
+        .aligned(|it| it.shingle(3).rsf_window::<true>(&cfg))
+        .collect();
+    assert_eq!(out.len(), 100);
+    assert_eq!(n_warm_up(&out), 3 + 32);
+    let plain: Vec<_> = x.outer_iter().shingle(3).rsf_window::<true>(&cfg).collect();
+    assert_eq!(
+        out[35..].iter().map(|s| s.unwrap()).collect::<Vec<_>>(),
+        plain
+    );
+}
+
+#[test]
+fn spot_warm_up() {
+    let scores: Vec<_> = (0..200)
+        .map(|i| AnomalyScore::new(((i * 37) % 100) as f32))
+        .collect();
+    let out: Vec<_> = scores
+        .into_iter()
+        .aligned(|it| it.spot(&SpotConfig::new(0.01, 50).depth(10)))
+        .collect();
+    assert_eq!(out.len(), 200);
+    assert_eq!(n_warm_up(&out), 60);
+}
+
+#[test]
+fn aligned_is_send() {
+    fn assert_send<T: Send>(_: &T) {}
+    let x = points(10);
+    let it = x.outer_iter().aligned(|it| it.shingle(3));
+    assert_send(&it);
+}
+
+#[test]
+#[should_panic(expected = "several outputs for one input")]
+fn several_outputs_per_input() {
+    let _ = (0..4).aligned(|it| it.flat_map(|i| [i, i])).count();
+}
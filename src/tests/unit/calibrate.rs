@@ -0,0 +1,59 @@
+use ndarray::prelude::*;
+
+use crate::{algorithm::bounding_box::BoundingBox, prelude::*};
+
+fn config() -> Config {
+    ConfigBuilder::default()
+        .bounding_box(BoundingBox::unit(1))
+        .n_points(64)
+        .n_trees(16)
+        .window(256)
+        .seed(0)
+        .build()
+}
+
+fn points(n: usize) -> Array2<f32> {
+    Array2::from_shape_fn((n, 1), |(i, _)| ((i * 37) % 100) as f32 / 100.)
+}
+
+// Anomaly probabilities in [0, 1] that never grow with the path length.
+fn check_monotone(cal: &Calibration) {
+    let scores: Vec<_> = (0..=40)
+        .map(|i| cal.apply(PathLength::new(i as f32 / 2.)).value())
+        .collect();
+    assert!(scores.iter().all(|p| (0. ..=1.).contains(p)));
+    assert!(scores.windows(2).all(|w| w[0] >= w[1]));
+}
+
+#[test]
+fn mass_is_monotone() {
+    check_monotone(&Calibration::from_mass(64.));
+    check_monotone(&Calibration::from_mass(1.));
+}
+
+#[test]
+fn empirical_is_monotone() {
+    let cfg = config();
+    let mut f = RSF::from_config(&cfg);
+    f.batch_insert(&points(64));
+    let cal = Calibration::empirical(&f, &points(200));
+    check_monotone(&cal);
+    // the least anomalous reference point is below all of them
+    assert_eq!(cal.apply(PathLength::new(f32::MAX)).value(), 0.);
+    assert_eq!(cal.apply(PathLength::new(0.)).value(), 1.);
+}
+
+// The window adapter calibrates with the mass its hash-picked trees actually hold.
+#[test]
+fn window_by_mass() {
+    let cfg = config();
+    let x = points(1000);
+    let raw: Vec<_> = x.outer_iter().rsf_window::<true>(&cfg).collect();
+    let mut it = x.outer_iter().rsf_window::<true>(&cfg).calibrate_by_mass();
+    let first = it.next().unwrap();
+    let cal = Calibration::from_forest(it.forest());
+    assert_eq!(first.value(), cal.apply(raw[0]).value());
+    let rest: Vec<_> = it.collect();
+    assert_eq!(rest.len(), raw.len() - 1);
+    assert!(rest.iter().all(|p| (0. ..=1.).contains(&p.value())));
+}
@@ -0,0 +1,97 @@
+use ndarray::prelude::*;
+
+use crate::{
+    algorithm::{bounding_box::BoundingBox, config::UpdateMode},
+    prelude::*,
+};
+
+fn config(update: UpdateMode) -> Config {
+    ConfigBuilder::default()
+        .bounding_box(BoundingBox::unit(2))
+        .n_points(32)
+        .n_trees(16)
+        .window(128)
+        .update(update)
+        .seed(3)
+        .build()
+}
+
+fn points(n: usize) -> Array2<f32> {
+    Array2::from_shape_fn((n, 2), |(i, j)| ((i * (7 + 6 * j)) % 100) as f32 / 100.)
+}
+
+// The push-style detectors behind the iterator adapters, fed the same seeded points.
+#[test]
+fn window_matches_adapter() {
+    let cfg = config(UpdateMode::ScoreThenUpdate);
+    let x = points(500);
+    let mut d = WindowDetector::<true>::new(&cfg);
+    let pushed: Vec<_> = x.outer_iter().map(|p| d.update(&p)).collect();
+    assert!(pushed[..128].iter().all(Option::is_none));
+    let pulled: Vec<_> = x.outer_iter().rsf_window::<true>(&cfg).collect();
+    assert_eq!(pushed.into_iter().flatten().collect::<Vec<_>>(), pulled);
+
+    let mut it = x.outer_iter().rsf_window::<true>(&cfg);
+    it.by_ref().for_each(drop);
+    let p = array![0.3, 0.7];
+    assert_eq!(d.score_only(&p), it.forest().score(&p));
+}
+
+#[test]
+fn reservoir_matches_adapter() {
+    let cfg = config(UpdateMode::ScoreThenUpdate);
+    let x = points(500);
+    let mut d = ReservoirDetector::<false>::new(&cfg);
+    let pushed: Vec<_> = x.outer_iter().map(|p| d.update(&p)).collect();
+    // uniform reservoirs are full after `n_points` points
+    assert!(pushed[..32].iter().all(Option::is_none));
+    assert!(pushed[32..].iter().all(Option::is_some));
+    let pulled: Vec<_> = x.outer_iter().rsf_reservoir::<false>(&cfg).collect();
+    assert_eq!(pushed.into_iter().flatten().collect::<Vec<_>>(), pulled);
+
+    let mut it = x.outer_iter().rsf_reservoir::<false>(&cfg);
+    it.by_ref().for_each(drop);
+    let p = array![0.3, 0.7];
+    assert_eq!(d.score_only(&p), it.forest().score(&p));
+}
+
+// Scoring only leaves the model as it was.
+#[test]
+fn score_only_does_not_update() {
+    let cfg = config(UpdateMode::ScoreThenUpdate);
+    let x = points(300);
+    let mut d1 = WindowDetector::<false>::new(&cfg);
+    let mut d2 = WindowDetector::<false>::new(&cfg);
+    for p in x.outer_iter() {
+        d1.score_only(&p);
+        assert_eq!(d1.update(&p), d2.update(&p));
+    }
+}
+
+// With `Delayed(d)` point `i` is scored against the model of points `0..i - d`, which is what a
+// score-then-update detector holds `d` points earlier; update-then-score includes point `i`.
+#[test]
+fn update_modes() {
+    let x = points(400);
+    let d = 5;
+    let mut reference = WindowDetector::<true>::new(&config(UpdateMode::ScoreThenUpdate));
+    let mut delayed = WindowDetector::<true>::new(&config(UpdateMode::Delayed(d)));
+    let mut immediate = WindowDetector::<true>::new(&config(UpdateMode::UpdateThenScore));
+    let mut after = WindowDetector::<true>::new(&config(UpdateMode::ScoreThenUpdate));
+    let mut n_scored = 0;
+    for (i, p) in x.outer_iter().enumerate() {
+        if let Some(s) = delayed.update(&p) {
+            assert_eq!(s, reference.score_only(&p));
+            n_scored += 1;
+        }
+        if i >= d {
+            reference.update(&x.row(i - d));
+        }
+
+        after.update(&p);
+        if let Some(s) = immediate.update(&p) {
+            assert_eq!(s, after.score_only(&p));
+        }
+    }
+    assert_eq!(n_scored, 400 - 128 - d);
+}
@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+
+use ndarray::prelude::*;
+
+use crate::{
+    adapter::distributed::{partial_forest, RoundCost},
+    prelude::*,
+};
+
+// 20 distinct points in 2 dimensions, so that every point takes 2 * 4 + 8 = 16 bytes.
+fn points() -> Array2<f32> {
+    Array::from_shape_fn((20, 2), |(i, j)| (i * (j + 1)) as f32 / 40.)
+}
+
+// 2 machines of 10 points, each sampling 4 of them in each of the 2 trees, with sketches large
+// enough to keep every point.
+fn config() -> Config {
+    ConfigBuilder::default()
+        .bounding_box(points().outer_iter().bb().unwrap())
+        .n_trees(2)
+        .n_points(8)
+        .n_machines(2)
+        .sketch_size(100)
+        .seed(0)
+        .build()
+}
+
+#[test]
+fn one_way_cost() {
+    let (_, _, report) = points()
+        .outer_iter()
+        .one_way_coordinator_with(&config(), 3, &TimeRange);
+    let machine = |machine| RoundCost {
+        round: 0,
+        machine,
+        points: 2 * 4,
+        sketch_weight: 2 * 4,
+        candidates: 3,
+        bytes_sent: (2 * 4 + 3) * 16,
+        bytes_received: 0,
+    };
+    assert_eq!(report.0, vec![machine(0), machine(1)]);
+    assert_eq!(report.bytes_sent(), 2 * 176);
+    assert_eq!(report.bytes(), 2 * 176);
+    assert_eq!(report.points(), 16);
+    assert_eq!(report.candidates(), 6);
+    assert_eq!(report.n_rounds(), 1);
+}
+
+#[test]
+fn two_way_cost() {
+    let (f, _, report) = points()
+        .outer_iter()
+        .two_way_par_streams_with(&config(), 3, &TimeRange);
+    assert_eq!(report.n_rounds(), 2);
+    // every point sent in the first round is answered with an 8-byte threshold
+    for c in report.round(0) {
+        assert_eq!((c.candidates, c.sketch_weight), (0, c.points));
+        assert_eq!(
+            (c.bytes_sent, c.bytes_received),
+            (c.points * 16, c.points * 8)
+        );
+    }
+    // then the sketch is broadcast, and every machine sends back 3 indices with their scores
+    let sketch_points: usize = f.points().iter().map(|t| t.len()).sum();
+    for c in report.round(1) {
+        assert_eq!((c.points, c.candidates), (0, 3));
+        assert_eq!(c.bytes_sent, 3 * (8 + 4));
+        assert_eq!(c.bytes_received, sketch_points * 16);
+    }
+    assert_eq!(report.machine(1).count(), 2);
+}
+
+fn blobs() -> Array2<f32> {
+    Array::from_shape_fn((400, 2), |(i, j)| ((i * (3 + j * 4)) % 97) as f32 / 97.)
+}
+
+fn hierarchy_config(n_machines: usize) -> Config {
+    ConfigBuilder::default()
+        .bounding_box(blobs().outer_iter().bb().unwrap())
+        .n_trees(4)
+        .n_points(256)
+        .n_machines(n_machines)
+        .sketch_size(1000)
+        .seed(0)
+        .build()
+}
+
+#[test]
+fn hierarchy_depth() {
+    let depths = |fan_in| {
+        let hierarchy = HierarchyConfig::new(fan_in, 1);
+        (1..=10).map(|n| hierarchy.depth(n)).collect::<Vec<_>>()
+    };
+    assert_eq!(depths(2), vec![1, 1, 2, 2, 3, 3, 3, 3, 4, 4]);
+    assert_eq!(depths(3), vec![1, 1, 1, 2, 2, 2, 2, 2, 2, 3]);
+    let cfg = hierarchy_config(8);
+    for fan_in in [2, 3, 8] {
+        let hierarchy = HierarchyConfig::new(fan_in, 2);
+        let (_, _, report) = blobs()
+            .outer_iter()
+            .hierarchical_coordinator_with(&cfg, 5, &hierarchy, &TimeRange);
+        assert_eq!(report.n_rounds(), hierarchy.depth(8), "fan-in {fan_in}");
+    }
+}
+
+#[test]
+fn flat_hierarchy_is_one_way() {
+    let cfg = hierarchy_config(4);
+    let (f, anomalies, report) = blobs()
+        .outer_iter()
+        .one_way_coordinator_with(&cfg, 5, &TimeRange);
+    for fan_in in [4, 8] {
+        let hierarchy = HierarchyConfig::new(fan_in, 1);
+        let (h_f, h_anomalies, h_report) = blobs()
+            .outer_iter()
+            .hierarchical_coordinator_with(&cfg, 5, &hierarchy, &TimeRange);
+        assert_eq!(h_anomalies, anomalies, "fan-in {fan_in}");
+        assert_eq!(h_report.0, report.0, "fan-in {fan_in}");
+        assert_eq!(h_f.points(), f.points(), "fan-in {fan_in}");
+    }
+}
+
+// Most points in one leaf of any tree of the forest.
+fn max_leaf_points(f: &RSF) -> usize {
+    (0..f.n_trees())
+        .map(|t| {
+            let tree = &f[t];
+            let mut counts = HashMap::new();
+            for (p, _) in tree.points() {
+                *counts.entry(tree.leaf(&p) as *const _).or_insert(0) += 1;
+            }
+            counts.into_values().max().unwrap_or(0)
+        })
+        .max()
+        .unwrap()
+}
+
+// The forests sent up by the aggregators are rebuilt level by level, checked against the cost
+// report, and must hold at most `sketch_size` points in every leaf.
+#[test]
+fn intermediate_levels_are_sketched() {
+    let cfg = hierarchy_config(8);
+    let hierarchy = HierarchyConfig::new(2, 2);
+    let (_, _, report) = blobs()
+        .outer_iter()
+        .hierarchical_coordinator_with(&cfg, 5, &hierarchy, &TimeRange);
+    let mut nodes: Vec<_> = blobs()
+        .outer_iter()
+        .distribute_with(&cfg, &TimeRange)
+        .into_iter()
+        .enumerate()
+        .map(|(m, mut points)| partial_forest(&cfg, m, &mut points, 5))
+        .collect();
+    // without sketches at the machines, their leaves hold more points
+    assert!(nodes
+        .iter()
+        .all(|f| max_leaf_points(f) > hierarchy.sketch_size));
+    for level in 1..hierarchy.depth(8) {
+        nodes = nodes
+            .chunks(hierarchy.fan_in)
+            .map(|children| {
+                let mut merged = RSF::from_config(&cfg);
+                children.iter().for_each(|f| merged.extend(f.clone()));
+                merged.sketch(hierarchy.sketch_size);
+                merged
+            })
+            .collect();
+        let points: Vec<_> = report.round(level).map(|c| c.points).collect();
+        let expected: Vec<_> = nodes
+            .iter()
+            .map(|f| f.points().iter().map(|t| t.len()).sum::<usize>())
+            .collect();
+        assert_eq!(points, expected, "level {level}");
+        assert!(nodes
+            .iter()
+            .all(|f| max_leaf_points(f) <= hierarchy.sketch_size));
+    }
+}
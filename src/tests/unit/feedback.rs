@@ -0,0 +1,135 @@
+use ndarray::prelude::*;
+
+use crate::{algorithm::bounding_box::BoundingBox, prelude::*};
+
+fn config(window: usize) -> Config {
+    ConfigBuilder::default()
+        .bounding_box(BoundingBox::unit(2))
+        .n_points(64)
+        .n_trees(32)
+        .window(window)
+        .seed(0)
+        .build()
+}
+
+fn point(i: usize) -> Array1<f32> {
+    array![
+        ((i * 7) % 100) as f32 / 400.,
+        ((i * 13) % 100) as f32 / 400.
+    ]
+}
+
+#[test]
+fn damped_cell_scores_lower_after_more_updates() {
+    let cfg = config(2048);
+    let outlier = array![0.9, 0.9];
+    let mut damped = WindowDetector::<true>::new(&cfg);
+    let mut plain = WindowDetector::<true>::new(&cfg);
+    for i in 0..1000 {
+        damped.update(&point(i));
+        plain.update(&point(i));
+    }
+    damped.damp(&outlier, 4.);
+    for i in 1000..2000 {
+        damped.update(&point(i));
+        plain.update(&point(i));
+    }
+    let (s_damped, s_plain) = (damped.score_only(&outlier), plain.score_only(&outlier));
+    assert!(
+        s_damped.value() > s_plain.value(),
+        "{s_damped:?} vs {s_plain:?}"
+    );
+    // normal points are unaffected
+    assert_eq!(damped.score_only(&point(3)), plain.score_only(&point(3)));
+}
+
+#[test]
+fn damping_expires_with_the_window() {
+    let cfg = config(256);
+    let outlier = array![0.9, 0.9];
+    let mut damped = WindowDetector::<true>::new(&cfg);
+    let mut plain = WindowDetector::<true>::new(&cfg);
+    damped.damp(&outlier, 4.);
+    for i in 0..300 {
+        damped.update(&point(i));
+        plain.update(&point(i));
+    }
+    assert_eq!(damped.score_only(&outlier), plain.score_only(&outlier));
+}
+
+#[test]
+fn normal_points_expire_with_the_window() {
+    let cfg = config(256);
+    let label = array![0.9, 0.9];
+    let mut d = WindowDetector::<true>::new(&cfg);
+    let mut other = WindowDetector::<true>::new(&cfg);
+    d.insert_normal(&label, 64);
+    other.insert_normal(&point(0), 64);
+    assert!(d.score_only(&label).value() > other.score_only(&label).value());
+    for i in 0..256 {
+        d.update(&point(i));
+        other.update(&point(i));
+    }
+    assert_eq!(d.score_only(&label), other.score_only(&label));
+}
+
+// A normal label sits next to the window, the real points stay in it.
+#[test]
+fn normal_points_do_not_evict_the_window() {
+    let cfg = config(256);
+    let mut d = WindowDetector::<false>::new(&cfg);
+    let mut plain = WindowDetector::<false>::new(&cfg);
+    for i in 0..256 {
+        d.update(&point(i));
+        plain.update(&point(i));
+    }
+    d.insert_normal(&array![0.9, 0.9], 64);
+    assert_eq!(d.forest().weight(), plain.forest().weight() + 64.);
+    for i in 256..300 {
+        d.update(&point(i));
+        plain.update(&point(i));
+    }
+    assert_eq!(d.forest().weight(), plain.forest().weight() + 64.);
+    assert_eq!(d.score_only(&point(3)), plain.score_only(&point(3)));
+}
+
+#[test]
+fn normal_points_expire_from_the_reservoir() {
+    let cfg = config(256);
+    let label = array![0.9, 0.9];
+    let mut d = ReservoirDetector::<true>::new(&cfg);
+    d.insert_normal(&label, 64);
+    assert_eq!(d.forest().weight(), 64.);
+    for i in 0..255 {
+        d.update(&point(i));
+    }
+    assert_eq!(d.forest().weight(), 128.);
+    for i in 255..10_000 {
+        d.update(&point(i));
+    }
+    assert_eq!(d.forest().weight(), 64.);
+}
+
+#[test]
+fn damping_expires_from_the_reservoir() {
+    let cfg = ConfigBuilder::default()
+        .bounding_box(BoundingBox::unit(2))
+        .n_points(64)
+        .n_trees(32)
+        .feedback_horizon(100)
+        .seed(0)
+        .build();
+    let outlier = array![0.9, 0.9];
+    let mut damped = ReservoirDetector::<true>::new(&cfg);
+    let mut plain = ReservoirDetector::<true>::new(&cfg);
+    damped.damp(&outlier, 4.);
+    for i in 0..99 {
+        damped.update(&point(i));
+        plain.update(&point(i));
+    }
+    let (s_damped, s_plain) = (damped.score_only(&outlier), plain.score_only(&outlier));
+    assert!(s_damped.value() > s_plain.value());
+    damped.update(&point(99));
+    plain.update(&point(99));
+    assert_eq!(damped.score_only(&outlier), plain.score_only(&outlier));
+}
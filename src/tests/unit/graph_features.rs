@@ -0,0 +1,71 @@
+use std::iter::once;
+
+use ndarray::prelude::*;
+
+use crate::adapter::{graph_features::GraphFeaturesIter, spotlight::Graph};
+
+#[test]
+fn densest_subgraph_of_clique_and_pendant() {
+    // sources and destinations are distinct nodes, so the clique on 4 nodes has 8 nodes and 12
+    // edges, and the pendant edge adds a ninth node
+    let mut edges: Vec<_> = (0..4)
+        .flat_map(|i| (0..4).filter(move |&j| j != i).map(move |j| (i, j, 1.)))
+        .collect();
+    edges.push((0, 99, 1.));
+    let out = once(Graph::new(edges)).densest_subgraph().next().unwrap();
+    assert_eq!(out, array![12. / 8., 8., 13. / 9.]);
+}
+
+#[test]
+fn densest_subgraph_of_nothing() {
+    let out = once(Graph::<u32, u32>::new(vec![]))
+        .densest_subgraph()
+        .next()
+        .unwrap();
+    assert_eq!(out, array![0., 0., 0.]);
+}
+
+#[test]
+fn distribution_of_star() {
+    // bins are [0, 2), [2, 4) and [4, inf)
+    let mut edges: Vec<_> = (10..15).map(|j| (0, j, 1.)).collect();
+    edges.push((1, 10, 2.5));
+    edges.push((2, 11, 5.));
+    let out = once(Graph::new(edges))
+        .degree_distribution(3)
+        .next()
+        .unwrap();
+    #[rustfmt::skip]
+    let expected = array![
+        2., 0., 1., // out-degrees 5, 1 and 1
+        3., 2., 0., // in-degrees 2, 2, 1, 1 and 1
+        5., 1., 1., // weights 1 five times, 2.5 and 5
+    ];
+    assert_eq!(out, expected);
+}
+
+#[test]
+fn count_min_of_single_source() {
+    let edges = vec![(0, 10, 1.), (0, 11, 2.), (0, 12, 3.)];
+    let (width, depth) = (8, 3);
+    let out = once(Graph::new(edges))
+        .count_min(width, depth, 0)
+        .next()
+        .unwrap();
+    let (src, dst) = out.view().split_at(Axis(0), width * depth);
+    for r in 0..depth {
+        let row = |s: ArrayView1<f32>| s.slice(s![r * width..(r + 1) * width]).to_owned();
+        // the one source puts all the weight in one bucket of each row
+        let src = row(src);
+        assert_eq!(src.sum(), 6.);
+        assert_eq!(src.iter().filter(|&&w| w > 0.).count(), 1);
+        assert_eq!(row(dst).sum(), 6.);
+    }
+}
+
+#[test]
+fn count_min_of_one_bucket() {
+    let edges = vec![(0, 10, 1.), (1, 11, 2.), (2, 10, 3.)];
+    let out = once(Graph::new(edges)).count_min(1, 2, 0).next().unwrap();
+    assert_eq!(out, array![6., 6., 6., 6.]);
+}
@@ -0,0 +1,49 @@
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::adapter::hash_picker::HashPicker;
+
+fn pickers(seed: u64) -> Vec<HashPicker> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..8)
+        .map(|_| HashPicker::from_frac(1, 4, &mut rng))
+        .collect()
+}
+
+fn picks(pickers: &[HashPicker]) -> Vec<Vec<usize>> {
+    pickers
+        .iter()
+        .map(|p| (0..1000usize).filter(|i| p.picks(i)).collect())
+        .collect()
+}
+
+#[test]
+fn same_seed_same_picks() {
+    assert_eq!(picks(&pickers(7)), picks(&pickers(7)));
+    assert_ne!(picks(&pickers(7)), picks(&pickers(8)));
+}
+
+#[test]
+fn picks_survive_serde() {
+    let pickers = pickers(7);
+    let json = serde_json::to_string(&pickers).unwrap();
+    let restored: Vec<HashPicker> = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored, pickers);
+    assert_eq!(picks(&restored), picks(&pickers));
+}
+
+#[test]
+fn usize_hashes_as_u64() {
+    // the buckets of `usize` keys must not depend on the pointer width
+    for p in pickers(7) {
+        assert!((0..1000).all(|i| p.bucket(&(i as usize)) == p.bucket(&(i as u64))));
+    }
+}
+
+// SipHash-1-3 with keys 1 and 2 of the 8 little-endian bytes of each key, modulo 10, as given by
+// a reference implementation
+#[test]
+fn known_buckets() {
+    let p: HashPicker = serde_json::from_str(r#"{"keys":[1,2],"num":1,"den":10}"#).unwrap();
+    let buckets: Vec<_> = (0..10usize).map(|i| p.bucket(&i)).collect();
+    assert_eq!(buckets, vec![1, 7, 5, 7, 4, 4, 2, 1, 5, 0]);
+}
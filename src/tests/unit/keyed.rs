@@ -0,0 +1,67 @@
+use ndarray::prelude::*;
+
+use crate::{algorithm::bounding_box::BoundingBox, prelude::*};
+
+fn config() -> Config {
+    ConfigBuilder::default()
+        .bounding_box(BoundingBox::unit(1))
+        .n_points(16)
+        .n_trees(4)
+        .window(32)
+        .seed(0)
+        .build()
+}
+
+fn run(keys: &[&'static str], keyed_cfg: &KeyedConfig) -> Vec<Option<bool>> {
+    let items: Vec<_> = keys.iter().map(|&k| (k, array![0.5])).collect();
+    let mut it = items
+        .iter()
+        .map(|(k, p)| (*k, p.view()))
+        .keyed::<true>(&config(), keyed_cfg);
+    it.by_ref().for_each(drop);
+    ["a", "b", "c"]
+        .iter()
+        .map(|k| keys.contains(k).then(|| it.forest(k).is_some()))
+        .collect()
+}
+
+#[test]
+fn idle_expiry() {
+    let cfg = KeyedConfig::default().idle(5);
+    // "a" was last seen 5 items ago, then 6
+    assert_eq!(run(&["a", "b", "b", "b", "b", "b"], &cfg)[0], Some(true));
+    assert_eq!(
+        run(&["a", "b", "b", "b", "b", "b", "b"], &cfg)[0],
+        Some(false)
+    );
+}
+
+#[test]
+fn least_recently_seen_evicted() {
+    let cfg = KeyedConfig::default().max_keys(2);
+    assert_eq!(
+        run(&["a", "b", "a", "c"], &cfg),
+        vec![Some(true), Some(false), Some(true)]
+    );
+}
+
+#[test]
+fn memory_cap_in_points() {
+    // every key holds 32 + 4 * 16 = 96 points
+    let cfg = KeyedConfig::default().max_points(200);
+    assert_eq!(
+        run(&["a", "b", "c"], &cfg),
+        vec![Some(false), Some(true), Some(true)]
+    );
+    let cfg = KeyedConfig::default().max_points(200).max_keys(1);
+    assert_eq!(
+        run(&["a", "b", "c"], &cfg),
+        vec![Some(false), Some(false), Some(true)]
+    );
+}
+
+#[test]
+#[should_panic(expected = "memory cap below a single key")]
+fn memory_cap_below_a_key() {
+    run(&["a"], &KeyedConfig::default().max_points(95));
+}
@@ -0,0 +1,57 @@
+use ndarray::prelude::*;
+
+use crate::prelude::*;
+
+#[test]
+fn range_metrics_perfect() {
+    let y = array![false, true, true, false, false, true, false];
+    assert_eq!(range_pr(&y, &y, Bias::Flat, Cardinality::One), 1.);
+    assert_eq!(range_rc(&y, &y, 0.5, Bias::Flat, Cardinality::One), 1.);
+    let first = array![false, true, false, false, false, true, false];
+    assert!((nab_score(&y, &first, NabProfile::Standard) - 100.).abs() < 1e-9);
+}
+
+#[test]
+fn range_pr_without_alarms() {
+    let y_true = array![false, true, true, false];
+    let y_alarm = Array1::from_elem(4, false);
+    assert_eq!(
+        range_pr(&y_true, &y_alarm, Bias::Flat, Cardinality::One),
+        0.
+    );
+}
+
+#[test]
+fn range_rc_without_labels() {
+    let y_true = Array1::from_elem(4, false);
+    let y_alarm = array![false, true, false, false];
+    let rc = range_rc(&y_true, &y_alarm, 0.5, Bias::Front, Cardinality::One);
+    assert_eq!(rc, 1.);
+}
+
+#[test]
+fn nab_score_without_labels() {
+    let y_true = Array1::from_elem(4, false);
+    let quiet = Array1::from_elem(4, false);
+    let noisy = array![false, true, false, false];
+    assert_eq!(nab_score(&y_true, &quiet, NabProfile::Standard), 100.);
+    assert_eq!(nab_score(&y_true, &noisy, NabProfile::Standard), 0.);
+}
+
+#[test]
+fn alarms_shorter_than_labels_are_aligned_to_the_end() {
+    let y_true = array![false, false, true, true];
+    let y_alarm = array![true, true];
+    assert_eq!(
+        range_pr(&y_true, &y_alarm, Bias::Flat, Cardinality::One),
+        1.
+    );
+}
+
+#[test]
+#[should_panic(expected = "more alarms than labels")]
+fn more_alarms_than_labels() {
+    let y_true = array![false, true];
+    let y_alarm = array![false, true, true];
+    range_pr(&y_true, &y_alarm, Bias::Flat, Cardinality::One);
+}
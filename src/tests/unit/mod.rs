@@ -0,0 +1,23 @@
+// Small deterministic checks of edge cases, next to the experiments.
+mod aligned;
+mod calibrate;
+mod detector;
+mod distributed;
+mod feedback;
+mod graph_features;
+mod hash_picker;
+mod keyed;
+mod metric;
+mod monitor;
+mod online_normalise;
+mod partition;
+mod period;
+mod remote;
+mod reservoir;
+mod score;
+mod shingle;
+mod spotlight;
+#[cfg(feature = "stream")]
+mod stream;
+mod threshold;
+mod time_window;
@@ -0,0 +1,79 @@
+use ndarray::prelude::*;
+
+use crate::{algorithm::bounding_box::BoundingBox, prelude::*};
+
+fn config() -> Config {
+    ConfigBuilder::default()
+        .bounding_box(BoundingBox::unit(1))
+        .n_points(32)
+        .n_trees(8)
+        .window(64)
+        .n_machines(2)
+        .seed(0)
+        .build()
+}
+
+// Points of site 0 only, site 1 stays silent.
+fn points(n: usize) -> Vec<(usize, Array1<f32>)> {
+    (0..n)
+        .map(|i| (0, array![(i % 100) as f32 / 100.]))
+        .collect()
+}
+
+#[test]
+fn sync_interval_sets_rounds() {
+    let items: Vec<_> = (0..300)
+        .map(|i| (i % 2, array![(i % 100) as f32 / 100.]))
+        .collect();
+    let mut mon = items
+        .iter()
+        .map(|(m, p)| (*m, p.view()))
+        .monitor::<ReservoirDetector<false>>(&config(), &MonitorConfig::new(50));
+    mon.by_ref().for_each(drop);
+    for m in 0..2 {
+        let rounds: Vec<_> = mon.cost().machine(m).map(|c| c.round).collect();
+        assert_eq!(rounds, vec![0, 1, 2]);
+        assert!(mon.cost().machine(m).all(|c| c.bytes_received > 0));
+    }
+    assert_eq!(mon.cost().n_rounds(), 3);
+}
+
+// The reply to the sync after point 50 is used from point 50 + staleness on, nothing is
+// emitted before.
+#[test]
+fn staleness_delays_global() {
+    let items = points(200);
+    for staleness in [0, 30] {
+        let mut mon = items
+            .iter()
+            .map(|(m, p)| (*m, p.view()))
+            .monitor::<ReservoirDetector<false>>(
+                &config(),
+                &MonitorConfig::new(50).staleness(staleness),
+            );
+        let n_out = mon.by_ref().count();
+        assert_eq!(n_out, 200 - 50 - staleness);
+        assert!(mon.global(0).is_some());
+        assert!(mon.global(1).is_none());
+    }
+
+    let mut mon = items[..79]
+        .iter()
+        .map(|(m, p)| (*m, p.view()))
+        .monitor::<ReservoirDetector<false>>(&config(), &MonitorConfig::new(50).staleness(30));
+    assert_eq!(mon.by_ref().count(), 0);
+    assert!(mon.global(0).is_none());
+}
+
+// Each sync replaces the previous contribution of the site instead of adding to it.
+#[test]
+fn global_replaces_site_contribution() {
+    let items = points(1000);
+    let mut mon = items
+        .iter()
+        .map(|(m, p)| (*m, p.view()))
+        .monitor::<ReservoirDetector<false>>(&config(), &MonitorConfig::new(100));
+    mon.by_ref().for_each(drop);
+    assert_eq!(mon.site(0).forest().weight(), 32.);
+    assert_eq!(mon.global(0).unwrap().weight(), 32.);
+}
@@ -0,0 +1,121 @@
+use ndarray::prelude::*;
+
+use crate::prelude::*;
+
+fn line(vals: &[f32]) -> Vec<Array1<f32>> {
+    vals.iter().map(|&v| array![v]).collect()
+}
+
+fn normalise(vals: &[f32], cfg: &OnlineNormaliseConfig) -> Vec<f32> {
+    line(vals)
+        .iter()
+        .map(|p| p.view())
+        .online_normalise(cfg)
+        .map(|p| p[0])
+        .collect()
+}
+
+fn assert_close(a: f32, b: f32) {
+    assert!((a - b).abs() < 1e-4, "{a} != {b}");
+}
+
+#[test]
+fn min_max_warm_up() {
+    let mut vals: Vec<f32> = (0..=10).map(|i| i as f32).collect();
+    vals.extend([5., 12.]);
+    let out = normalise(&vals, &OnlineNormaliseConfig::new(Scaling::MinMax, 11));
+    assert_close(out[0], 0.);
+    assert_close(out[10], 1.);
+    assert_close(out[11], 0.5);
+    // clamped to the padded box
+    assert_close(out[12], 1.1);
+}
+
+#[test]
+fn z_score_warm_up() {
+    let mut vals = vec![1., 3., 1., 3.];
+    vals.push(4.);
+    let out = normalise(&vals, &OnlineNormaliseConfig::new(Scaling::ZScore, 4));
+    assert_close(out[0], -1.);
+    assert_close(out[1], 1.);
+    // 4 is beyond the padded box [-1.2, 1.2]
+    assert_close(out[4], 1.2);
+}
+
+// The warm-up sets the exact quantiles, and only later points move them.
+#[test]
+fn quantile_warm_up() {
+    let mut vals: Vec<f32> = (0..=100).map(|i| i as f32).collect();
+    vals.push(50.);
+    let cfg = OnlineNormaliseConfig::new(Scaling::Quantile(0.1, 0.9), 101);
+    let out = normalise(&vals, &cfg);
+    assert_close(out[10], 0.);
+    assert_close(out[90], 1.);
+    assert_close(out[101], 0.5);
+
+    vals.push(200.);
+    let updates: Vec<_> = line(&vals)
+        .iter()
+        .map(|p| p.view())
+        .online_normalise_rescaled(&cfg)
+        .collect();
+    // one SGD step per point after the warm-up
+    let lr = 1. / 101.;
+    let (mut lo, mut hi) = (10_f32, 90_f32);
+    for v in [50., 200.] {
+        let step = lr * (hi - lo);
+        lo += step * (0.1 - if v < lo { 1. } else { 0. });
+        hi += step * (0.9 - if v < hi { 1. } else { 0. });
+    }
+    match updates.last() {
+        Some(NormaliseUpdate::Rescale(_, p)) => assert_close(p[0], (200. - lo) / (hi - lo)),
+        _ => panic!("no rescale"),
+    }
+}
+
+#[test]
+fn rescale_event() {
+    let mut vals: Vec<f32> = (0..=10).map(|i| i as f32).collect();
+    vals.extend([11., 20., 5.]);
+    let cfg = OnlineNormaliseConfig::new(Scaling::MinMax, 11);
+    let mut it = line(&vals).into_iter().online_normalise_rescaled(&cfg);
+    let raw = it.raw_bounding_box().unwrap();
+    assert_close(raw.bounds[[0, 0]], -1.);
+    assert_close(raw.bounds[[0, 1]], 11.);
+    let updates: Vec<_> = it.by_ref().collect();
+    assert_eq!(updates.len(), 14);
+    let values: Vec<_> = updates
+        .iter()
+        .map(|u| match u {
+            NormaliseUpdate::Point(p) => (false, p[0]),
+            NormaliseUpdate::Rescale(_, p) => (true, p[0]),
+        })
+        .collect();
+    assert!(values[..12].iter().all(|&(rescale, _)| !rescale));
+    assert_close(values[11].1, 1.1);
+    assert!(values[12].0);
+    assert_close(values[12].1, 1.);
+    assert_close(values[13].1, 0.25);
+    if let NormaliseUpdate::Rescale(bb, _) = &updates[12] {
+        assert_close(bb.bounds[[0, 0]], -0.1);
+        assert_close(bb.bounds[[0, 1]], 1.1);
+    }
+    // the raw frame follows the rescale
+    assert_close(it.raw_bounding_box().unwrap().bounds[[0, 1]], 22.);
+}
+
+#[test]
+fn empty_and_short_streams() {
+    let cfg = OnlineNormaliseConfig::new(Scaling::MinMax, 8);
+    let mut it = line(&[]).into_iter().online_normalise(&cfg);
+    assert!(it.bounding_box().is_none());
+    assert!(it.next().is_none());
+    assert_eq!(
+        line(&[])
+            .into_iter()
+            .online_normalise_rescaled(&cfg)
+            .count(),
+        0
+    );
+    assert_eq!(normalise(&[1., 2., 3.], &cfg).len(), 3);
+}
@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+
+use ndarray::prelude::*;
+
+use crate::adapter::partition::{HashByKey, Points};
+use crate::prelude::*;
+
+fn points(n: usize) -> Points {
+    (0..n).map(|i| (i, array![i as f32])).collect()
+}
+
+fn indices<P: Partitioner>(partitioner: &P, n: usize, n_machines: usize) -> Vec<Vec<usize>> {
+    partitioner
+        .partition(points(n), n_machines)
+        .into_iter()
+        .map(|machine| machine.into_iter().map(|(i, _)| i).collect())
+        .collect()
+}
+
+#[test]
+fn round_robin() {
+    let expected = vec![vec![0, 3, 6], vec![1, 4], vec![2, 5]];
+    assert_eq!(indices(&RoundRobin, 7, 3), expected);
+}
+
+#[test]
+fn time_range() {
+    let expected = vec![vec![0, 1, 2], vec![3, 4, 5], vec![6]];
+    assert_eq!(indices(&TimeRange, 7, 3), expected);
+    // more machines than points leaves the last ones empty
+    let expected = vec![vec![0], vec![1], vec![], vec![]];
+    assert_eq!(indices(&TimeRange, 2, 4), expected);
+}
+
+#[test]
+fn skewed() {
+    let expected = vec![vec![0, 3, 4, 5], vec![1, 6], vec![2]];
+    assert_eq!(indices(&Skewed::new([4, 5]), 7, 3), expected);
+}
+
+#[test]
+fn hash_by_key_keeps_keys_together() {
+    let partitioner = HashByKey::new(|i, _: &Array1<f32>| i % 10, 0);
+    let machines = indices(&partitioner, 200, 4);
+    let mut machine_of = HashMap::new();
+    for (m, machine) in machines.iter().enumerate() {
+        for i in machine {
+            assert_eq!(*machine_of.entry(i % 10).or_insert(m), m, "key {}", i % 10);
+        }
+    }
+    assert_eq!(machines.iter().map(|m| m.len()).sum::<usize>(), 200);
+}
+
+#[test]
+fn random_partitioners_are_seeded() {
+    let ranges = indices(&RandomRanges::new(7), 100, 4);
+    assert_eq!(ranges, indices(&RandomRanges::new(7), 100, 4));
+    // contiguous ranges, in order
+    assert_eq!(ranges.concat(), (0..100).collect::<Vec<_>>());
+    let machines = indices(&RandomMachines::new(7), 100, 4);
+    assert_eq!(machines, indices(&RandomMachines::new(7), 100, 4));
+    assert_ne!(machines, indices(&RandomMachines::new(8), 100, 4));
+}
+
+#[test]
+#[should_panic(expected = "no machines to partition between")]
+fn random_ranges_of_no_machines() {
+    RandomRanges::new(0).partition(points(10), 0);
+}
+
+#[test]
+#[should_panic(expected = "no machines to partition between")]
+fn random_machines_of_no_machines() {
+    RandomMachines::new(0).partition(points(10), 0);
+}
+
+#[test]
+fn seeded_coordinator_is_reproducible() {
+    let x = Array::from_shape_fn((200, 2), |(i, j)| ((i * 7 + j * 13) % 50) as f32 / 50.);
+    let cfg = ConfigBuilder::default()
+        .bounding_box(x.outer_iter().bb().unwrap())
+        .n_trees(8)
+        .n_points(64)
+        .n_machines(4)
+        .seed(0)
+        .build();
+    let (_, a, _) = x.outer_iter().one_way_coordinator(&cfg, 5);
+    let (_, b, _) = x.outer_iter().one_way_coordinator(&cfg, 5);
+    assert_eq!(a, b);
+}
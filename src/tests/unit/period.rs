@@ -0,0 +1,35 @@
+use std::f32::consts::PI;
+
+use ndarray_rand::rand_distr::{Distribution, Normal};
+use rand::{prelude::StdRng, SeedableRng};
+
+use crate::period::detect_period;
+
+fn noise(n: usize, std: f32, seed: u64) -> Vec<f32> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    Normal::new(0., std)
+        .unwrap()
+        .sample_iter(&mut rng)
+        .take(n)
+        .collect()
+}
+
+#[test]
+fn period_of_noisy_sinusoid() {
+    for period in [7, 24, 50, 100] {
+        let values: Vec<f32> = noise(1024, 0.3, period as u64)
+            .into_iter()
+            .enumerate()
+            .map(|(i, e)| (2. * PI * i as f32 / period as f32).sin() + e)
+            .collect();
+        let detected = detect_period(&values).expect("no period detected");
+        assert!(detected.abs_diff(period) <= 1, "{detected} for {period}");
+    }
+}
+
+#[test]
+fn no_period_in_white_noise() {
+    for seed in 0..10 {
+        assert_eq!(detect_period(&noise(1024, 1., seed)), None, "seed {seed}");
+    }
+}
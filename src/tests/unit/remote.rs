@@ -0,0 +1,122 @@
+use std::{
+    io::{self, ErrorKind, Write},
+    net::{TcpListener, TcpStream},
+    thread,
+};
+
+use crate::{
+    adapter::remote::{
+        max_payload, one_way_coordinator, recv, send, ForestSketch, Message, PROTOCOL_VERSION,
+    },
+    algorithm::bounding_box::BoundingBox,
+    prelude::*,
+};
+
+fn config() -> Config {
+    ConfigBuilder::default()
+        .bounding_box(BoundingBox::unit(2))
+        .n_trees(4)
+        .n_points(16)
+        .n_machines(2)
+        .seed(0)
+        .build()
+}
+
+fn partial(machine: usize) -> Message {
+    Message::Partial {
+        machine,
+        sketch: ForestSketch(vec![vec![(vec![0.5, 0.5], 1)]; 4]),
+        candidates: vec![(machine, vec![0.1, 0.9])],
+    }
+}
+
+fn frame(msg: &Message) -> Vec<u8> {
+    let mut buf = Vec::new();
+    send(&mut buf, msg).unwrap();
+    buf
+}
+
+#[test]
+fn frames_round_trip() {
+    let mut buf = Vec::new();
+    let n_bytes = send(&mut buf, &partial(1)).unwrap();
+    let (Message::Partial { machine, .. }, n_read) = recv(&mut buf.as_slice(), u64::MAX).unwrap();
+    assert_eq!((machine, n_read), (1, n_bytes));
+}
+
+#[test]
+fn oversized_payload_is_refused() {
+    let max = max_payload(&config(), 1);
+    let mut frame = PROTOCOL_VERSION.to_be_bytes().to_vec();
+    frame.extend((max + 1).to_be_bytes());
+    let err = recv(&mut frame.as_slice(), max).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn largest_partial_fits_payload() {
+    // 8 sampled points in each of the 4 trees, with the longest numbers JSON can print
+    let cfg = config();
+    let n1 = 3;
+    let p = vec![-f32::MIN_POSITIVE; 2];
+    let msg = Message::Partial {
+        machine: usize::MAX,
+        sketch: ForestSketch(vec![vec![(p.clone(), usize::MAX); 8]; 4]),
+        candidates: vec![(usize::MAX, p); n1],
+    };
+    let n_bytes = send(&mut Vec::new(), &msg).unwrap();
+    assert!((n_bytes - 12) as u64 <= max_payload(&cfg, n1));
+}
+
+// Sends the frames one connection after the other, and returns the machines the coordinator
+// kept.
+fn coordinate(frames: Vec<Vec<u8>>) -> io::Result<Vec<usize>> {
+    let cfg = config();
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let addr = listener.local_addr()?;
+    let workers = thread::spawn(move || {
+        for frame in frames {
+            // the coordinator may hang up early on a bad frame
+            let _ = TcpStream::connect(addr).and_then(|mut s| s.write_all(&frame));
+        }
+    });
+    let res = one_way_coordinator(&listener, &cfg, 1)
+        .map(|(_, _, cost)| cost.0.iter().map(|c| c.machine).collect());
+    drop(listener);
+    workers.join().unwrap();
+    res
+}
+
+#[test]
+fn coordinator_drops_bad_connections() {
+    assert_eq!(
+        coordinate(vec![frame(&partial(0)), frame(&partial(1))]).unwrap(),
+        vec![0, 1]
+    );
+    let mut oversized = PROTOCOL_VERSION.to_be_bytes().to_vec();
+    oversized.extend(u64::MAX.to_be_bytes());
+    let mut truncated = frame(&partial(1));
+    truncated.truncate(truncated.len() / 2);
+    let Message::Partial {
+        sketch, candidates, ..
+    } = partial(1);
+    let wrong_dim = Message::Partial {
+        machine: 1,
+        sketch,
+        candidates: candidates
+            .into_iter()
+            .map(|(i, _)| (i, vec![0.5]))
+            .collect(),
+    };
+    let frames = vec![
+        frame(&partial(0)),
+        frame(&partial(2)),
+        frame(&partial(0)),
+        b"not a frame".to_vec(),
+        oversized,
+        truncated,
+        frame(&wrong_dim),
+        frame(&partial(1)),
+    ];
+    assert_eq!(coordinate(frames).unwrap(), vec![0, 1]);
+}
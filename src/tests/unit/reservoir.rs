@@ -0,0 +1,32 @@
+use ndarray::prelude::*;
+
+use crate::{
+    algorithm::{bounding_box::BoundingBox, config::Sampling},
+    prelude::*,
+};
+
+// The first score comes once every reservoir holds `n_points` points, however many points it
+// took to get there.
+fn first_score_with_full_reservoirs(sampling: Sampling) {
+    let cfg = ConfigBuilder::default()
+        .bounding_box(BoundingBox::unit(1))
+        .n_points(32)
+        .n_trees(8)
+        .sampling(sampling)
+        .seed(0)
+        .build();
+    let mut d = ReservoirDetector::<true>::new(&cfg);
+    let mut n = 0;
+    while d.update(&array![(n % 100) as f32 / 100.]).is_none() {
+        n += 1;
+    }
+    assert!(n >= 32);
+    assert_eq!(d.forest().weight(), 32.);
+}
+
+#[test]
+fn warm_up_on_fill() {
+    first_score_with_full_reservoirs(Sampling::Uniform);
+    first_score_with_full_reservoirs(Sampling::Biased(64.));
+    first_score_with_full_reservoirs(Sampling::Priority(64.));
+}
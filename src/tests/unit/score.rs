@@ -0,0 +1,34 @@
+use ndarray::prelude::*;
+
+use crate::{
+    metric::top_k,
+    score::{AnomalyScore, PathLength},
+};
+
+#[test]
+fn orientation() {
+    assert!(PathLength::new(1.) > PathLength::new(2.));
+    assert!(AnomalyScore::new(2.) > AnomalyScore::new(1.));
+    assert_eq!(PathLength::from_anomaly(3.).value(), -3.);
+    assert_eq!(PathLength::from_anomaly(3.).anomaly(), 3.);
+    assert_eq!(AnomalyScore::from_anomaly(3.).value(), 3.);
+    assert!(PathLength::from_anomaly(2.) > PathLength::from_anomaly(1.));
+}
+
+#[test]
+fn nan_and_zeros() {
+    assert_eq!(PathLength::new(f32::NAN), PathLength::new(f32::NAN));
+    assert!(PathLength::new(f32::NAN) > PathLength::new(f32::NEG_INFINITY));
+    assert!(AnomalyScore::new(f32::NAN) > AnomalyScore::new(f32::INFINITY));
+    assert!(AnomalyScore::new(-f32::NAN) > AnomalyScore::new(f32::INFINITY));
+    assert_eq!(PathLength::new(-0.), PathLength::new(0.));
+    assert_eq!(AnomalyScore::new(-0.), AnomalyScore::new(0.));
+}
+
+#[test]
+fn top_k_is_most_anomalous() {
+    let lengths = array![3., 1., 2., 5.].mapv(PathLength::new);
+    assert_eq!(top_k(&lengths, 2), vec![1, 2]);
+    let anomalies = array![3., 1., f32::NAN, 5.].mapv(AnomalyScore::new);
+    assert_eq!(top_k(&anomalies, 3), vec![2, 3, 0]);
+}
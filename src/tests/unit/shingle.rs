@@ -0,0 +1,48 @@
+use ndarray::prelude::*;
+
+use crate::{algorithm::bounding_box::BoundingBox, prelude::*};
+
+fn in_box(transform: ShingleTransform) {
+    let bb = BoundingBox::new(array![[1., 2.], [0.5, 4.]]);
+    let shingle_cfg = ShingleConfig::new(vec![1, 3]).transform(transform);
+    let cfg = ConfigBuilder::default()
+        .bounding_box(bb)
+        .multi_shingle(&shingle_cfg)
+        .build();
+    assert_eq!(cfg.bb.d(), 6);
+    let x = Array2::from_shape_fn((50, 2), |(i, j)| {
+        if j == 0 {
+            1. + ((i * 7) % 10) as f32 / 10.
+        } else {
+            0.5 + ((i * 3) % 8) as f32 / 2.
+        }
+    });
+    for p in x.outer_iter().multi_shingle(&shingle_cfg) {
+        assert!(cfg.bb.contains(&p), "{p} outside {:?}", cfg.bb.bounds);
+    }
+}
+
+#[test]
+fn multi_shingles_within_config_box() {
+    in_box(ShingleTransform::Raw);
+    in_box(ShingleTransform::Diff);
+    in_box(ShingleTransform::Ratio);
+}
+
+#[test]
+fn consecutive_shingle_box() {
+    let cfg = ConfigBuilder::default()
+        .bounding_box(BoundingBox::unit(2))
+        .shingle(3)
+        .build();
+    assert_eq!((cfg.shingle, cfg.bb.d()), (3, 6));
+}
+
+#[test]
+#[should_panic(expected = "ratio shingles need strictly positive lower bounds")]
+fn ratio_shingles_need_positive_bounds() {
+    ConfigBuilder::default()
+        .bounding_box(BoundingBox::unit(2))
+        .multi_shingle(&ShingleConfig::new(vec![1]).transform(ShingleTransform::Ratio))
+        .build();
+}
@@ -0,0 +1,97 @@
+use crate::prelude::*;
+
+fn edges(ts: impl Iterator<Item = u64>) -> Vec<(u64, u32, u32, f32)> {
+    ts.enumerate()
+        .map(|(i, t)| (t, (i % 37) as u32, (i % 53) as u32, 1.))
+        .collect()
+}
+
+#[test]
+fn sliding_buckets_are_full() {
+    let cfg = SpotLightConfig::new(20, 0.3, 0.3).seed(0);
+    let buckets = BucketConfig::sliding(300, 100);
+    let out: Vec<_> = (1000..4000)
+        .map(|t| (t / 10 * 10, 0u32, 0u32, 1.))
+        .spotlight_edges(&cfg, &buckets)
+        .collect();
+    let starts: Vec<_> = out.iter().map(|(t, _)| *t).collect();
+    assert_eq!(starts, (1000..=3700).step_by(100).collect::<Vec<_>>());
+    // the same edge comes at a constant rate, so all the full buckets are the same
+    assert!(out.iter().all(|(_, s)| s == out[0].1));
+}
+
+#[test]
+fn long_gaps_are_skipped() {
+    let cfg = SpotLightConfig::new(20, 0.3, 0.3).seed(0);
+    let buckets = BucketConfig::sliding(300, 100);
+    let ts = (0..1000).chain((0..1000).map(|t| u64::MAX / 2 + t));
+    let out: Vec<_> = edges(ts)
+        .into_iter()
+        .spotlight_edges(&cfg, &buckets)
+        .collect();
+    assert!(out.len() < 30, "{} buckets", out.len());
+    assert_eq!(
+        out.last().unwrap().0,
+        (u64::MAX / 2 + 999) / 100 * 100 - 200
+    );
+}
+
+#[test]
+fn attribution_of_no_dimension() {
+    let cfg = SpotLightConfig::new(20, 0.3, 0.3).seed(0).heavy_hitters(4);
+    let buckets = BucketConfig::tumbling(100);
+    let (_, _, attribution) = edges(0..300)
+        .into_iter()
+        .spotlight_edges_attributed(&cfg, &buckets)
+        .next()
+        .unwrap();
+    assert!(attribution.top_src(&[], 3).is_empty());
+    assert!(attribution.top_dst(&[], 3).is_empty());
+    let dims = attribution.top_dims(20);
+    assert_eq!(dims.len(), 20);
+    assert!(attribution.top_src(&dims, 0).is_empty());
+    assert!(attribution.top_dst(&dims, 3).len() <= 3);
+}
+
+#[test]
+fn late_edges_go_to_the_current_step() {
+    let cfg = SpotLightConfig::new(20, 0.3, 0.3).seed(0);
+    let buckets = BucketConfig::tumbling(100);
+    let in_order: Vec<_> = [(0, 1), (150, 2), (160, 3), (250, 4)]
+        .map(|(t, i)| (t, i as u32, i as u32, 1.))
+        .into_iter()
+        .spotlight_edges(&cfg, &buckets)
+        .collect();
+    // the third edge comes after the second one but is stamped in the first bucket
+    let late: Vec<_> = [(0, 1), (150, 2), (10, 3), (250, 4)]
+        .map(|(t, i)| (t, i as u32, i as u32, 1.))
+        .into_iter()
+        .spotlight_edges(&cfg, &buckets)
+        .collect();
+    assert_eq!(late, in_order);
+}
+
+#[test]
+fn edges_near_the_end_of_time() {
+    let cfg = SpotLightConfig::new(20, 0.3, 0.3).seed(0);
+    let buckets = BucketConfig::sliding(4, 1);
+    let out: Vec<_> = edges(u64::MAX - 9..=u64::MAX)
+        .into_iter()
+        .spotlight_edges(&cfg, &buckets)
+        .collect();
+    let starts: Vec<_> = out.iter().map(|(t, _)| *t).collect();
+    assert_eq!(starts, (u64::MAX - 9..=u64::MAX - 3).collect::<Vec<_>>());
+}
+
+#[test]
+fn trailing_partial_buckets_are_not_emitted() {
+    let cfg = SpotLightConfig::new(20, 0.3, 0.3).seed(0);
+    let buckets = BucketConfig::sliding(300, 100);
+    let out: Vec<_> = edges(0..450)
+        .into_iter()
+        .spotlight_edges(&cfg, &buckets)
+        .collect();
+    // the last bucket ends with the step of the last edge, even if that step is not over
+    let starts: Vec<_> = out.iter().map(|(t, _)| *t).collect();
+    assert_eq!(starts, vec![0, 100, 200]);
+}
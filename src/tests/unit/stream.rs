@@ -0,0 +1,77 @@
+use std::task::Poll;
+
+use futures::{executor::block_on, stream, Stream, StreamExt};
+use ndarray::prelude::*;
+
+use crate::{algorithm::bounding_box::BoundingBox, prelude::*};
+
+fn points() -> Vec<Array1<f32>> {
+    (0..600)
+        .map(|i| array![((i * 7) % 100) as f32 / 100., ((i * 13) % 50) as f32 / 50.])
+        .collect()
+}
+
+fn config() -> Config {
+    ConfigBuilder::default()
+        .bounding_box(BoundingBox::unit(2))
+        .shingle(3)
+        .n_points(32)
+        .n_trees(16)
+        .window(64)
+        .seed(0)
+        .build()
+}
+
+// Yields every item only after the stream has been pending once, as a socket would.
+fn pending_every_other<T>(items: Vec<T>) -> impl Stream<Item = T> + Unpin {
+    let mut items = items.into_iter();
+    let mut ready = false;
+    stream::poll_fn(move |cx| {
+        ready = !ready;
+        if ready {
+            Poll::Ready(items.next())
+        } else {
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    })
+}
+
+#[test]
+fn window_stream_scores_like_the_iterator() {
+    let cfg = config();
+    let sync: Vec<_> = points()
+        .iter()
+        .map(|p| p.view())
+        .shingle(3)
+        .rsf_window::<true>(&cfg)
+        .transform(&cfg)
+        .collect();
+    let from_iter = stream::iter(points())
+        .shingle(3)
+        .rsf_window::<true>(&cfg)
+        .transform(&cfg)
+        .collect::<Vec<_>>();
+    let from_pending = pending_every_other(points())
+        .shingle(3)
+        .rsf_window::<true>(&cfg)
+        .transform(&cfg)
+        .collect::<Vec<_>>();
+    assert!(!sync.is_empty());
+    assert_eq!(block_on(from_iter), sync);
+    assert_eq!(block_on(from_pending), sync);
+}
+
+#[test]
+fn reservoir_stream_scores_like_the_iterator() {
+    let cfg = config();
+    let shingled: Vec<_> = points().iter().map(|p| p.view()).shingle(3).collect();
+    let sync: Vec<_> = shingled
+        .iter()
+        .map(|p| p.view())
+        .rsf_reservoir::<false>(&cfg)
+        .collect();
+    let from_pending = pending_every_other(shingled).rsf_reservoir::<false>(&cfg);
+    assert!(!sync.is_empty());
+    assert_eq!(block_on(from_pending.collect::<Vec<_>>()), sync);
+}
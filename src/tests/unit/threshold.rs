@@ -0,0 +1,47 @@
+use ndarray::prelude::*;
+use ndarray_rand::{rand_distr::Uniform, RandomExt};
+use rand::{prelude::StdRng, SeedableRng};
+
+use crate::prelude::*;
+
+// Pareto scores of scale 1 and shape 5, so that P(X > z) = z^-5, i.e. a GPD tail of shape 1/5.
+fn pareto(n: usize, seed: u64) -> Vec<AnomalyScore> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    Array1::random_using(n, Uniform::new(0f32, 1.), &mut rng)
+        .iter()
+        .map(|u| AnomalyScore::new((1. - u).powf(-0.2)))
+        .collect()
+}
+
+#[test]
+fn spot_threshold_on_pareto_tail() {
+    let q = 1e-3;
+    let cfg = SpotConfig::new(q, 20_000);
+    let mut spot = pareto(20_001, 0).into_iter().spot(&cfg);
+    spot.next().unwrap();
+    let z = spot.threshold().unwrap().anomaly() as f64;
+    let expected = q.powf(-0.2);
+    assert!(
+        (z - expected).abs() / expected < 0.15,
+        "threshold {z}, expected {expected}"
+    );
+}
+
+#[test]
+fn spot_alarm_rate_on_pareto_tail() {
+    let q = 1e-2;
+    let cfg = SpotConfig::new(q, 10_000);
+    let alarms: Vec<bool> = pareto(110_000, 1).into_iter().spot(&cfg).collect();
+    assert_eq!(alarms.len(), 100_000);
+    let rate = alarms.iter().filter(|&&a| a).count() as f64 / alarms.len() as f64;
+    assert!(q / 2. < rate && rate < 2. * q, "alarm rate {rate}");
+}
+
+#[test]
+fn spot_on_stream_shorter_than_calibration() {
+    let cfg = SpotConfig::new(1e-3, 1000).depth(10);
+    let mut spot = pareto(500, 2).into_iter().spot(&cfg);
+    assert_eq!(spot.next(), None);
+    assert!(spot.threshold().is_none());
+    assert_eq!(pareto(500, 2).into_iter().spot_proba(&cfg).count(), 0);
+}
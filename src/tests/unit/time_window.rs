@@ -0,0 +1,135 @@
+use ndarray::prelude::*;
+
+use crate::{
+    algorithm::{bounding_box::BoundingBox, config::UpdateMode},
+    prelude::*,
+};
+
+fn config(update: UpdateMode) -> Config {
+    ConfigBuilder::default()
+        .bounding_box(BoundingBox::unit(1))
+        .n_points(16)
+        .n_trees(8)
+        .window(1 << 20)
+        .update(update)
+        .seed(0)
+        .build()
+}
+
+fn point(i: usize) -> Array1<f32> {
+    array![((i * 37) % 100) as f32 / 100.]
+}
+
+fn stream(n: usize, dt: u64) -> Vec<(u64, Array1<f32>)> {
+    (0..n).map(|i| (i as u64 * dt, point(i))).collect()
+}
+
+// Scores start once the first point has expired, a full span after it.
+#[test]
+fn expiry_by_time() {
+    let cfg = config(UpdateMode::ScoreThenUpdate);
+    for dt in [1, 3] {
+        let items = stream(500, dt);
+        let n_out = items
+            .iter()
+            .map(|(t, p)| (*t, p.view()))
+            .rsf_time_window::<true>(&cfg, 100)
+            .count();
+        assert_eq!(n_out, 500 - 100_usize.div_ceil(dt as usize));
+    }
+
+    // after a long gap only the latest points are left
+    let mut items = stream(200, 1);
+    items.extend((0..10).map(|i| (10_000 + i as u64, point(i))));
+    let mut it = items
+        .iter()
+        .map(|(t, p)| (*t, p.view()))
+        .rsf_time_window::<false>(&cfg, 100);
+    it.by_ref().for_each(drop);
+    assert_eq!(it.forest().weight(), 10.);
+}
+
+#[test]
+fn capped_window() {
+    let cfg = config(UpdateMode::ScoreThenUpdate);
+    let items = stream(500, 1);
+    let n_out = items
+        .iter()
+        .map(|(t, p)| (*t, p.view()))
+        .rsf_time_window_capped::<true>(&cfg, 1_000_000, 32)
+        .count();
+    assert_eq!(n_out, 500 - 32);
+
+    // the config window caps the plain variant
+    let cfg = ConfigBuilder::default()
+        .bounding_box(BoundingBox::unit(1))
+        .n_points(16)
+        .n_trees(8)
+        .window(64)
+        .build();
+    let n_out = items
+        .iter()
+        .map(|(t, p)| (*t, p.view()))
+        .rsf_time_window::<true>(&cfg, 1_000_000)
+        .count();
+    assert_eq!(n_out, 500 - 64);
+}
+
+#[test]
+#[should_panic(expected = "window smaller than the sample")]
+fn cap_below_sample() {
+    let items = stream(10, 1);
+    let cfg = config(UpdateMode::ScoreThenUpdate);
+    let _ = items
+        .iter()
+        .map(|(t, p)| (*t, p.view()))
+        .rsf_time_window_capped::<true>(&cfg, 100, 8);
+}
+
+#[test]
+fn update_modes() {
+    // update-then-score scores each point against a model that already holds it
+    let items = stream(300, 1);
+    let mut it = items
+        .iter()
+        .map(|(t, p)| (*t, p.view()))
+        .rsf_time_window::<false>(&config(UpdateMode::UpdateThenScore), 50);
+    for k in 0..250 {
+        let s = it.next().unwrap();
+        assert_eq!(s, it.forest().score(&point(50 + k)));
+    }
+
+    // a delayed window holds the points of an immediate one `d` points later
+    let probe = array![0.5];
+    let no_expiry = |update, n| {
+        let items = stream(n, 1);
+        let mut it = items
+            .iter()
+            .map(|(t, p)| (*t, p.view()))
+            .rsf_time_window::<true>(&config(update), 1_000_000);
+        it.by_ref().for_each(drop);
+        it.forest().score(&probe)
+    };
+    assert_eq!(
+        no_expiry(UpdateMode::ScoreThenUpdate, 200),
+        no_expiry(UpdateMode::Delayed(5), 205)
+    );
+}
+
+#[test]
+fn feedback() {
+    let cfg = config(UpdateMode::ScoreThenUpdate);
+    let items = stream(500, 1);
+    let mut it = items
+        .iter()
+        .map(|(t, p)| (*t, p.view()))
+        .rsf_time_window::<false>(&cfg, 100);
+    it.by_ref().take(150).for_each(drop);
+    let weight = it.forest().weight();
+    it.insert_normal(&array![0.995], 8);
+    assert_eq!(it.forest().weight(), weight + 8.);
+    it.exclude(&point(240));
+    // excluded points are skipped when they leave the window, the normal label expires
+    it.by_ref().for_each(drop);
+    assert_eq!(it.forest().weight(), 16.);
+}
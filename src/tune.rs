@@ -0,0 +1,252 @@
+use std::{fmt, time::Instant};
+
+use itertools::iproduct;
+use ndarray::{Array1, Array2};
+use rand::Rng;
+use rayon::prelude::*;
+
+use crate::{
+    adapter::{rsf_window::RSFWindowIter, shingle::ShingleIter, transform::TransformIter},
+    algorithm::{
+        bounding_box::BoundingBoxIter,
+        config::{Config, ConfigBuilder},
+    },
+    metric::prauc,
+    score::AnomalyScore,
+};
+
+// Trials whose means are closer than this are tied, e.g. stability scores, which have no spread.
+const TIE_TOLERANCE: f64 = 1e-3;
+
+#[derive(Clone)]
+pub struct TuneGrid {
+    pub n_points: Vec<usize>,
+    pub n_trees: Vec<usize>,
+    pub granularity: Vec<usize>,
+    pub window: Vec<usize>,
+    pub shingle: Vec<usize>,
+    pub n_repetitions: usize,
+}
+
+impl Default for TuneGrid {
+    fn default() -> Self {
+        Self {
+            n_points: vec![128, 256, 512],
+            n_trees: vec![32, 64],
+            granularity: vec![1, 4],
+            window: vec![1024, 2048],
+            shingle: vec![1],
+            n_repetitions: 4,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Criterion {
+    PrAuc,
+    Stability,
+}
+
+#[derive(Clone, Debug)]
+pub struct Trial {
+    pub n_points: usize,
+    pub n_trees: usize,
+    pub granularity: usize,
+    pub window: usize,
+    pub shingle: usize,
+    pub mean: f64,
+    pub std: f64,
+    pub secs: f64,
+}
+
+impl Trial {
+    fn cost(&self) -> usize {
+        self.n_points * self.n_trees
+    }
+}
+
+pub struct TuneReport {
+    pub criterion: Criterion,
+    pub trials: Vec<Trial>,
+    pub best: usize,
+}
+
+impl TuneReport {
+    pub fn best(&self) -> &Trial {
+        &self.trials[self.best]
+    }
+}
+
+impl fmt::Display for TuneReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "criterion: {:?}", self.criterion)?;
+        writeln!(
+            f,
+            "n_points\tn_trees\tgranularity\twindow\tshingle\tscore\ttime (s)"
+        )?;
+        for (i, t) in self.trials.iter().enumerate() {
+            write!(
+                f,
+                "{}\t{}\t{}\t{}\t{}\t{:.3} ({:.3})\t{:.2}",
+                t.n_points, t.n_trees, t.granularity, t.window, t.shingle, t.mean, t.std, t.secs
+            )?;
+            writeln!(f, "{}", if i == self.best { "\t*" } else { "" })?;
+        }
+        Ok(())
+    }
+}
+
+fn run(x: &Array2<f32>, cfg: &Config) -> Array1<AnomalyScore> {
+    x.outer_iter()
+        .shingle(cfg.shingle)
+        .rsf_window::<true>(cfg)
+        .transform(cfg)
+        .collect()
+}
+
+// Tied scores, common since path lengths are averages of integers, share their average rank.
+fn ranks(scores: &Array1<AnomalyScore>) -> Array1<f64> {
+    let mut args: Vec<usize> = (0..scores.len()).collect();
+    args.sort_unstable_by_key(|&i| scores[i]);
+    let mut ranks = Array1::zeros(scores.len());
+    let mut start = 0;
+    for tied in args.chunk_by(|&i, &j| scores[i] == scores[j]) {
+        let rank = start as f64 + (tied.len() - 1) as f64 / 2.;
+        tied.iter().for_each(|&i| ranks[i] = rank);
+        start += tied.len();
+    }
+    ranks
+}
+
+// Spearman correlation between the scores of independently seeded runs.
+fn stability(runs: &[Array1<AnomalyScore>]) -> f64 {
+    let ranks: Vec<_> = runs.iter().map(ranks).collect();
+    let mut sum = 0.;
+    let mut n_pairs = 0;
+    for (i, r1) in ranks.iter().enumerate() {
+        for r2 in &ranks[i + 1..] {
+            let (m1, m2) = (r1.mean().unwrap(), r2.mean().unwrap());
+            let (d1, d2) = (r1 - m1, r2 - m2);
+            let den = (d1.dot(&d1) * d2.dot(&d2)).sqrt();
+            sum += if den == 0. { 0. } else { d1.dot(&d2) / den };
+            n_pairs += 1;
+        }
+    }
+    sum / (n_pairs as f64)
+}
+
+fn mean_std(vals: &[f64]) -> (f64, f64) {
+    let n = vals.len() as f64;
+    let mean = vals.iter().sum::<f64>() / n;
+    let var = vals.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+    (mean, var.sqrt())
+}
+
+// Index of the cheapest trial within one standard deviation of the best one, the best mean
+// breaking cost ties.
+pub fn best_trial(trials: &[Trial]) -> usize {
+    let top = trials
+        .iter()
+        .max_by(|t1, t2| t1.mean.total_cmp(&t2.mean))
+        .expect("no trials");
+    let min_mean = top.mean - top.std.max(TIE_TOLERANCE);
+    (0..trials.len())
+        .filter(|&i| trials[i].mean >= min_mean)
+        .min_by(|&i, &j| {
+            let (ti, tj) = (&trials[i], &trials[j]);
+            ti.cost().cmp(&tj.cost()).then(tj.mean.total_cmp(&ti.mean))
+        })
+        .unwrap()
+}
+
+// Grid search over the streaming configuration. With labels, trials are ranked by PR-AUC;
+// without, by how consistently independently seeded forests rank the points. The cheapest
+// configuration within one standard deviation of the best one is picked.
+pub fn tune(
+    x: &Array2<f32>,
+    y_true: Option<&Array1<bool>>,
+    cb: &ConfigBuilder,
+    grid: &TuneGrid,
+) -> (Config, TuneReport) {
+    let n = x.dim().0;
+    let bb = x.outer_iter().bb().expect("empty dataset");
+    let criterion = if y_true.is_some() {
+        Criterion::PrAuc
+    } else {
+        assert!(
+            grid.n_repetitions > 1,
+            "stability needs several repetitions"
+        );
+        Criterion::Stability
+    };
+
+    // Every trial is run with the same seeds, drawn from the builder's seed if it has one.
+    let mut rng = cb.clone().bounding_box(bb.clone()).build().get_rng();
+    let seeds: Vec<u64> = (0..grid.n_repetitions).map(|_| rng.gen()).collect();
+
+    let combinations: Vec<_> = iproduct!(
+        grid.n_points.iter().copied(),
+        grid.n_trees.iter().copied(),
+        grid.granularity.iter().copied(),
+        grid.window.iter().copied(),
+        grid.shingle.iter().copied()
+    )
+    .filter(|&(n_points, _, _, window, shingle)| n_points <= window && window + shingle < n)
+    .collect();
+    assert!(!combinations.is_empty(), "no feasible configuration");
+
+    let trials: Vec<Trial> = combinations
+        .into_par_iter()
+        .map(|(n_points, n_trees, granularity, window, shingle)| {
+            let cb = cb
+                .clone()
+                .bounding_box(bb.clone())
+                .n_points(n_points)
+                .n_trees(n_trees)
+                .granularity(granularity)
+                .window(window)
+                .shingle(shingle);
+            let now = Instant::now();
+            let runs: Vec<_> = seeds
+                .iter()
+                .map(|&seed| run(x, &cb.clone().seed(seed).build()))
+                .collect();
+            let secs = now.elapsed().as_secs_f64() / (grid.n_repetitions as f64);
+            let (mean, std) = match y_true {
+                Some(y_true) => {
+                    let vals: Vec<_> = runs.iter().map(|s| prauc(y_true, s)).collect();
+                    mean_std(&vals)
+                }
+                None => (stability(&runs), 0.),
+            };
+            Trial {
+                n_points,
+                n_trees,
+                granularity,
+                window,
+                shingle,
+                mean,
+                std,
+                secs,
+            }
+        })
+        .collect();
+
+    let best = best_trial(&trials);
+    let t = &trials[best];
+    let cfg = cb
+        .clone()
+        .bounding_box(bb)
+        .n_points(t.n_points)
+        .n_trees(t.n_trees)
+        .granularity(t.granularity)
+        .window(t.window)
+        .shingle(t.shingle)
+        .build();
+    let report = TuneReport {
+        criterion,
+        trials,
+        best,
+    };
+    (cfg, report)
+}